@@ -1,15 +1,211 @@
+mod schema;
+
+pub use schema::{
+    AccessDefinition, AnalyzerDefinition, Bm25, Changefeed, DefinitionMode, EventDefinition,
+    FieldDefinition, IndexDefinition, IndexKind, PermissionDefinition, PermissionOperation,
+    PermissionRule, SearchIndex, TableDefinition, TableType, VectorAlgorithm, VectorIndex,
+};
+
 pub trait SurQLSchemaProducer {
     fn schema_query() -> &'static str;
+
+    /// The statements of `schema_query`, one per entry, to apply them one at
+    /// a time. Manual implementations of `schema_query` have none.
+    fn schema_statements() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// The structured schema the query is rendered from. Manual
+    /// implementations of `schema_query` have none.
+    fn schema_definition() -> Option<TableDefinition> {
+        None
+    }
+
+    /// The kind of record id, inferred from the Rust type of the `id` field.
+    fn record_id_kind() -> Option<RecordIdKind> {
+        None
+    }
+
+    /// The schema query preceded by the namespace and database it belongs
+    /// to. Falls back to `schema_query` when no scope is declared.
+    fn scoped_schema_query() -> &'static str {
+        Self::schema_query()
+    }
+}
+
+/// The table of a type deriving `SurQLDefinition`, from which relations
+/// pointing to it infer their `IN` and `OUT` tables.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no table to infer the relation table from",
+    label = "derive `SurQLDefinition` or set IN/OUT explicitly in surql_table_options"
+)]
+pub trait SurQLTable {
+    const TABLE_NAME: &'static str;
+}
+
+/// The shape of the id part of a record id, such as `user:⟨...⟩`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordIdKind {
+    String,
+    Integer,
+    Uuid,
+    Array,
+    Object,
+    /// A full record id such as `Thing`, or an id of any kind.
+    Any,
+}
+
+impl RecordIdKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(Self::String),
+            "int" => Some(Self::Integer),
+            "uuid" => Some(Self::Uuid),
+            "array" => Some(Self::Array),
+            "object" => Some(Self::Object),
+            "any" => Some(Self::Any),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Integer => "int",
+            Self::Uuid => "uuid",
+            Self::Array => "array",
+            Self::Object => "object",
+            Self::Any => "any",
+        }
+    }
+
+    /// The SurrealQL function generating ids of this kind, to be used as
+    /// `table:rand()`. Integer and composite ids have to be provided.
+    pub fn generator(&self) -> Option<&'static str> {
+        match self {
+            Self::String => Some("rand()"),
+            Self::Uuid => Some("uuid()"),
+            _ => None,
+        }
+    }
+}
+
+/// Conversion of a Rust value into a SurrealQL literal, used to build calls
+/// to functions generated with `surql_function`.
+pub trait ToSurQLLiteral {
+    fn to_surql_literal(&self) -> String;
+}
+
+macro_rules! impl_display_literal {
+    ($($ty:ty),*) => {
+        $(impl ToSurQLLiteral for $ty {
+            fn to_surql_literal(&self) -> String {
+                self.to_string()
+            }
+        })*
+    };
+}
+
+impl_display_literal!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, bool);
+
+macro_rules! impl_float_literal {
+    ($($ty:ty),*) => {
+        $(impl ToSurQLLiteral for $ty {
+            fn to_surql_literal(&self) -> String {
+                if self.is_finite() && self.fract() == 0.0 {
+                    format!("{:.1}", self)
+                } else {
+                    self.to_string()
+                }
+            }
+        })*
+    };
+}
+
+impl_float_literal!(f32, f64);
+
+impl ToSurQLLiteral for str {
+    fn to_surql_literal(&self) -> String {
+        let mut result = String::with_capacity(self.len() + 2);
+        result.push('"');
+        for c in self.chars() {
+            match c {
+                '\\' => result.push_str("\\\\"),
+                '"' => result.push_str("\\\""),
+                '\n' => result.push_str("\\n"),
+                '\r' => result.push_str("\\r"),
+                '\t' => result.push_str("\\t"),
+                _ => result.push(c),
+            }
+        }
+        result.push('"');
+        result
+    }
+}
+
+impl ToSurQLLiteral for String {
+    fn to_surql_literal(&self) -> String {
+        self.as_str().to_surql_literal()
+    }
+}
+
+impl ToSurQLLiteral for char {
+    fn to_surql_literal(&self) -> String {
+        self.to_string().to_surql_literal()
+    }
+}
+
+impl<T: ToSurQLLiteral + ?Sized> ToSurQLLiteral for &T {
+    fn to_surql_literal(&self) -> String {
+        (**self).to_surql_literal()
+    }
+}
+
+impl<T: ToSurQLLiteral> ToSurQLLiteral for Option<T> {
+    fn to_surql_literal(&self) -> String {
+        match self {
+            Some(value) => value.to_surql_literal(),
+            None => "NONE".to_string(),
+        }
+    }
+}
+
+impl<T: ToSurQLLiteral> ToSurQLLiteral for [T] {
+    fn to_surql_literal(&self) -> String {
+        let values = self
+            .iter()
+            .map(ToSurQLLiteral::to_surql_literal)
+            .collect::<Vec<_>>();
+        format!("[{}]", values.join(", "))
+    }
+}
+
+impl<T: ToSurQLLiteral, const N: usize> ToSurQLLiteral for [T; N] {
+    fn to_surql_literal(&self) -> String {
+        self.as_slice().to_surql_literal()
+    }
+}
+
+impl<T: ToSurQLLiteral> ToSurQLLiteral for Vec<T> {
+    fn to_surql_literal(&self) -> String {
+        self.as_slice().to_surql_literal()
+    }
 }
 
+/// Converts a type name to snake case. Acronyms are kept together, so
+/// `HTTPRequestLog` becomes `http_request_log`.
 pub fn to_snake_case(s: &str) -> String {
     let mut result = String::new();
     let mut last_char_was_upper = false;
     let mut last_char_was_letter = false;
+    let mut chars = s.chars().peekable();
 
-    for c in s.chars() {
+    while let Some(c) = chars.next() {
         if c.is_ascii_uppercase() {
-            if last_char_was_letter && !last_char_was_upper {
+            // The last capital of an acronym starts the next word
+            let ends_acronym =
+                last_char_was_upper && chars.peek().is_some_and(char::is_ascii_lowercase);
+            if last_char_was_letter && (!last_char_was_upper || ends_acronym) {
                 result.push('_');
             }
             result.push(c.to_ascii_lowercase());
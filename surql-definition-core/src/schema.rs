@@ -0,0 +1,596 @@
+use std::fmt;
+
+use crate::ToSurQLLiteral;
+
+/// How a `DEFINE` statement behaves when the resource already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DefinitionMode {
+    #[default]
+    Default,
+    IfNotExists,
+    Overwrite,
+}
+
+impl DefinitionMode {
+    /// Renders `DEFINE <kind>` followed by the mode keyword, if any.
+    pub fn define(&self, kind: &str) -> String {
+        match self {
+            DefinitionMode::Default => format!("DEFINE {}", kind),
+            DefinitionMode::IfNotExists => format!("DEFINE {} IF NOT EXISTS", kind),
+            DefinitionMode::Overwrite => format!("DEFINE {} OVERWRITE", kind),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableType {
+    Any,
+    Normal,
+    Relation {
+        in_tables: Option<String>,
+        out_tables: Option<String>,
+        enforced: bool,
+    },
+}
+
+impl fmt::Display for TableType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TableType::Any => write!(f, "TYPE ANY"),
+            TableType::Normal => write!(f, "TYPE NORMAL"),
+            TableType::Relation {
+                in_tables,
+                out_tables,
+                enforced,
+            } => {
+                write!(f, "TYPE RELATION")?;
+                if let Some(in_tables) = in_tables {
+                    write!(f, " IN {}", in_tables)?;
+                }
+                if let Some(out_tables) = out_tables {
+                    write!(f, " OUT {}", out_tables)?;
+                }
+                if *enforced {
+                    write!(f, " ENFORCED")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Changefeed {
+    pub duration: String,
+    pub include_original: bool,
+}
+
+impl fmt::Display for Changefeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CHANGEFEED {}", self.duration)?;
+        if self.include_original {
+            write!(f, " INCLUDE ORIGINAL")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionOperation {
+    Select,
+    Create,
+    Update,
+    Delete,
+}
+
+impl fmt::Display for PermissionOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operation = match self {
+            PermissionOperation::Select => "select",
+            PermissionOperation::Create => "create",
+            PermissionOperation::Update => "update",
+            PermissionOperation::Delete => "delete",
+        };
+        write!(f, "{}", operation)
+    }
+}
+
+/// A `FOR <operations> <condition>` clause, where the condition is `FULL`,
+/// `NONE` or a `WHERE` clause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionRule {
+    pub operations: Vec<PermissionOperation>,
+    pub condition: String,
+}
+
+/// The `PERMISSIONS` of a table or field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermissionDefinition {
+    None,
+    Full,
+    Rules(Vec<PermissionRule>),
+}
+
+impl fmt::Display for PermissionDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PermissionDefinition::None => write!(f, "NONE"),
+            PermissionDefinition::Full => write!(f, "FULL"),
+            PermissionDefinition::Rules(rules) => {
+                let rules = rules
+                    .iter()
+                    .map(|rule| {
+                        let operations = rule
+                            .operations
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        format!("FOR {} {}", operations, rule.condition)
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "{}", rules.join(" "))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldDefinition {
+    pub name: String,
+    pub flexible: bool,
+    pub field_type: Option<String>,
+    pub default: Option<String>,
+    pub value: Option<String>,
+    pub readonly: bool,
+    pub assertion: Option<String>,
+    pub permissions: Option<PermissionDefinition>,
+    pub comment: Option<String>,
+}
+
+impl FieldDefinition {
+    /// The `DEFINE FIELD` statement of the field on `table_name`.
+    pub fn render(&self, table_name: &str, mode: DefinitionMode) -> String {
+        format!(
+            "{} {} ON {}{}",
+            mode.define("FIELD"),
+            self.name,
+            table_name,
+            self.render_clauses()
+        )
+    }
+
+    /// Everything following the table name in the `DEFINE FIELD` statement,
+    /// including the terminating `;`.
+    pub fn render_clauses(&self) -> String {
+        let mut clauses = String::new();
+
+        if self.flexible {
+            clauses.push_str(" FLEXIBLE");
+        }
+        if let Some(field_type) = self.field_type.as_ref().filter(|ty| !ty.is_empty()) {
+            clauses.push_str(&format!(" TYPE {}", field_type));
+        }
+        if let Some(default) = &self.default {
+            clauses.push_str(&format!(" DEFAULT {}", default));
+        }
+        if let Some(value) = &self.value {
+            clauses.push_str(&format!(" VALUE {}", value));
+        }
+        if self.readonly {
+            clauses.push_str(" READONLY");
+        }
+        if let Some(assertion) = &self.assertion {
+            clauses.push_str(&format!(" ASSERT {}", assertion));
+        }
+        if let Some(permissions) = &self.permissions {
+            clauses.push_str(&format!(" PERMISSIONS {}", permissions));
+        }
+        if let Some(comment) = &self.comment {
+            clauses.push_str(&format!(" COMMENT {}", comment.to_surql_literal()));
+        }
+        if !clauses.ends_with(';') {
+            clauses.push(';');
+        }
+
+        clauses
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bm25 {
+    Default,
+    Parameters { k1: String, b: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchIndex {
+    pub analyzer: String,
+    pub bm25: Option<Bm25>,
+    pub highlights: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VectorAlgorithm {
+    Mtree,
+    Hnsw,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VectorIndex {
+    pub algorithm: VectorAlgorithm,
+    pub dimension: Option<usize>,
+    pub vector_type: Option<String>,
+    pub distance: Option<String>,
+    pub capacity: Option<usize>,
+    pub efc: Option<usize>,
+    pub m: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexKind {
+    Standard,
+    Unique,
+    Search(SearchIndex),
+    Vector(VectorIndex),
+}
+
+impl fmt::Display for IndexKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexKind::Standard => Ok(()),
+            IndexKind::Unique => write!(f, " UNIQUE"),
+            IndexKind::Search(search) => {
+                write!(f, " SEARCH ANALYZER {}", search.analyzer)?;
+                match &search.bm25 {
+                    Some(Bm25::Parameters { k1, b }) => write!(f, " BM25({},{})", k1, b)?,
+                    Some(Bm25::Default) => write!(f, " BM25")?,
+                    None => {}
+                }
+                if search.highlights {
+                    write!(f, " HIGHLIGHTS")?;
+                }
+                Ok(())
+            }
+            IndexKind::Vector(vector) => {
+                match vector.algorithm {
+                    VectorAlgorithm::Mtree => write!(f, " MTREE")?,
+                    VectorAlgorithm::Hnsw => write!(f, " HNSW")?,
+                }
+                if let Some(dimension) = vector.dimension {
+                    write!(f, " DIMENSION {}", dimension)?;
+                }
+                if let Some(vector_type) = &vector.vector_type {
+                    write!(f, " TYPE {}", vector_type)?;
+                }
+                if let Some(distance) = &vector.distance {
+                    write!(f, " DIST {}", distance)?;
+                }
+                if let Some(capacity) = vector.capacity {
+                    write!(f, " CAPACITY {}", capacity)?;
+                }
+                if let Some(efc) = vector.efc {
+                    write!(f, " EFC {}", efc)?;
+                }
+                if let Some(m) = vector.m {
+                    write!(f, " M {}", m)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexDefinition {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub kind: IndexKind,
+}
+
+impl IndexDefinition {
+    /// The `DEFINE INDEX` statement of the index on `table_name`.
+    pub fn render(&self, table_name: &str, mode: DefinitionMode) -> String {
+        format!(
+            "{} {} ON TABLE {} FIELDS {}{};",
+            mode.define("INDEX"),
+            self.name,
+            table_name,
+            self.fields.join(", "),
+            self.kind
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnalyzerDefinition {
+    pub name: String,
+    pub tokenizers: Vec<String>,
+    pub filters: Vec<String>,
+}
+
+impl AnalyzerDefinition {
+    /// The `DEFINE ANALYZER` statement of the analyzer.
+    pub fn render(&self, mode: DefinitionMode) -> String {
+        let mut query = format!("{} {}", mode.define("ANALYZER"), self.name);
+        if !self.tokenizers.is_empty() {
+            query.push_str(&format!(" TOKENIZERS {}", self.tokenizers.join(",")));
+        }
+        if !self.filters.is_empty() {
+            query.push_str(&format!(" FILTERS {}", self.filters.join(",")));
+        }
+        query.push(';');
+        query
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventDefinition {
+    pub name: String,
+    pub when: Option<String>,
+    pub then: String,
+}
+
+impl EventDefinition {
+    /// The `DEFINE EVENT` statement of the event on `table_name`.
+    pub fn render(&self, table_name: &str, mode: DefinitionMode) -> String {
+        let mut query = format!(
+            "{} {} ON TABLE {}",
+            mode.define("EVENT"),
+            self.name,
+            table_name
+        );
+        if let Some(when) = &self.when {
+            query.push_str(&format!(" WHEN {}", when));
+        }
+
+        // Statements such as CREATE must be wrapped to be used as the event action
+        let then = self.then.trim().trim_end_matches(';');
+        if then.starts_with('(') || then.starts_with('{') {
+            query.push_str(&format!(" THEN {}", then));
+        } else {
+            query.push_str(&format!(" THEN ({})", then));
+        }
+        query.push(';');
+        query
+    }
+}
+
+/// Record access signing users up and in with the fields of their table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessDefinition {
+    pub name: String,
+    /// The field users sign in with.
+    pub identifier: String,
+    /// The field holding the argon2 hash of the password.
+    pub password: String,
+    pub session: Option<String>,
+    pub token: Option<String>,
+    /// Renders a SurrealDB 1.x `DEFINE SCOPE` instead of `DEFINE ACCESS`.
+    pub legacy_scope: bool,
+}
+
+impl AccessDefinition {
+    /// The `DEFINE ACCESS` statement of the access on `table_name`. Every
+    /// field except `id` and computed ones is set on signup.
+    pub fn render(
+        &self,
+        table_name: &str,
+        fields: &[FieldDefinition],
+        mode: DefinitionMode,
+    ) -> String {
+        let assignments = fields
+            .iter()
+            .filter(|field| field.name != "id" && field.value.is_none())
+            .map(|field| {
+                if field.name == self.password {
+                    format!("{} = crypto::argon2::generate(${})", field.name, field.name)
+                } else {
+                    format!("{} = ${}", field.name, field.name)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let signup = format!("SIGNUP (CREATE {} SET {})", table_name, assignments);
+        let signin = format!(
+            "SIGNIN (SELECT * FROM {} WHERE {} = ${} AND crypto::argon2::compare({}, ${}))",
+            table_name, self.identifier, self.identifier, self.password, self.password
+        );
+
+        if self.legacy_scope {
+            let mut query = format!("{} {}", mode.define("SCOPE"), self.name);
+            if let Some(session) = &self.session {
+                query.push_str(&format!(" SESSION {}", session));
+            }
+            format!("{} {} {};", query, signup, signin)
+        } else {
+            let mut query = format!(
+                "{} {} ON DATABASE TYPE RECORD {} {}",
+                mode.define("ACCESS"),
+                self.name,
+                signup,
+                signin
+            );
+            let durations = [("TOKEN", &self.token), ("SESSION", &self.session)]
+                .iter()
+                .filter_map(|(kind, duration)| {
+                    duration
+                        .as_ref()
+                        .map(|duration| format!("FOR {} {}", kind, duration))
+                })
+                .collect::<Vec<_>>();
+            if !durations.is_empty() {
+                query.push_str(&format!(" DURATION {}", durations.join(", ")));
+            }
+            format!("{};", query)
+        }
+    }
+}
+
+/// The structured form of a table schema. Its `Display` implementation
+/// renders the same query as `SurQLSchemaProducer::schema_query`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableDefinition {
+    pub name: String,
+    pub mode: DefinitionMode,
+    pub drop: bool,
+    pub table_type: Option<TableType>,
+    /// The query of a view table, whose fields aren't defined.
+    pub view: Option<String>,
+    pub changefeed: Option<Changefeed>,
+    pub permissions: Option<PermissionDefinition>,
+    pub comment: Option<String>,
+    pub fields: Vec<FieldDefinition>,
+    pub analyzers: Vec<AnalyzerDefinition>,
+    pub indexes: Vec<IndexDefinition>,
+    pub events: Vec<EventDefinition>,
+    pub accesses: Vec<AccessDefinition>,
+    /// The statements of `surql_query`, replacing every statement above.
+    pub custom_query: Option<Vec<String>>,
+    pub queries_before: Vec<String>,
+    pub queries_after: Vec<String>,
+}
+
+impl TableDefinition {
+    /// The `DEFINE TABLE` statement.
+    pub fn render_table(&self) -> String {
+        let mut query = format!("{} {}", self.mode.define("TABLE"), self.name);
+        if self.drop {
+            query.push_str(" DROP");
+        }
+        if let Some(table_type) = &self.table_type {
+            query.push_str(&format!(" {}", table_type));
+        }
+        if let Some(view) = &self.view {
+            query.push_str(&format!(" AS {}", view));
+        }
+        if let Some(changefeed) = &self.changefeed {
+            query.push_str(&format!(" {}", changefeed));
+        }
+        if let Some(permissions) = &self.permissions {
+            query.push_str(&format!(" PERMISSIONS {}", permissions));
+        }
+        if let Some(comment) = &self.comment {
+            query.push_str(&format!(" COMMENT {}", comment.to_surql_literal()));
+        }
+        query.push(';');
+        query
+    }
+
+    /// Adds the analyzers, indexes and events of the definition of a
+    /// flattened field, whose fields are placed by the caller. Indexes named
+    /// after the flattened table are renamed after this one.
+    ///
+    /// # Panics
+    ///
+    /// When an analyzer, index or event of the same name is already defined
+    /// differently.
+    pub fn include_flattened(&mut self, flattened: TableDefinition) {
+        for analyzer in flattened.analyzers {
+            include_named(
+                &mut self.analyzers,
+                analyzer,
+                |a| &a.name,
+                "Analyzer",
+                &self.name,
+            );
+        }
+        let prefix = format!("{}_", flattened.name);
+        for mut index in flattened.indexes {
+            if let Some(rest) = index.name.strip_prefix(&prefix) {
+                index.name = format!("{}_{}", self.name, rest);
+            }
+            include_named(&mut self.indexes, index, |i| &i.name, "Index", &self.name);
+        }
+        for event in flattened.events {
+            include_named(&mut self.events, event, |e| &e.name, "Event", &self.name);
+        }
+    }
+
+    /// Every statement of the schema, in order.
+    pub fn render_statements(&self) -> Vec<String> {
+        let mut statements: Vec<String> = self
+            .queries_before
+            .iter()
+            .map(|query| terminate_statement(query))
+            .collect();
+
+        if let Some(custom_query) = &self.custom_query {
+            statements.extend(custom_query.iter().map(|query| terminate_statement(query)));
+        } else {
+            statements.push(self.render_table());
+            // The shape of a view is owned by its query
+            if self.view.is_none() {
+                statements.extend(
+                    self.fields
+                        .iter()
+                        .map(|field| field.render(&self.name, self.mode)),
+                );
+            }
+            statements.extend(
+                self.analyzers
+                    .iter()
+                    .map(|analyzer| analyzer.render(self.mode)),
+            );
+            statements.extend(
+                self.indexes
+                    .iter()
+                    .map(|index| index.render(&self.name, self.mode)),
+            );
+            statements.extend(
+                self.events
+                    .iter()
+                    .map(|event| event.render(&self.name, self.mode)),
+            );
+            statements.extend(
+                self.accesses
+                    .iter()
+                    .map(|access| access.render(&self.name, &self.fields, self.mode)),
+            );
+        }
+
+        statements.extend(
+            self.queries_after
+                .iter()
+                .map(|query| terminate_statement(query)),
+        );
+        statements
+    }
+}
+
+impl fmt::Display for TableDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render_statements().join(" "))
+    }
+}
+
+/// Adds `item` unless an identical one is already there.
+fn include_named<T: PartialEq>(
+    items: &mut Vec<T>,
+    item: T,
+    name: impl Fn(&T) -> &String,
+    kind: &str,
+    table: &str,
+) {
+    match items.iter().find(|existing| name(existing) == name(&item)) {
+        Some(existing) if *existing == item => {}
+        Some(_) => panic!(
+            "{} `{}` is defined differently on table `{}` through flattened fields",
+            kind,
+            name(&item),
+            table
+        ),
+        None => items.push(item),
+    }
+}
+
+/// Ends a raw statement with `;` unless it already is.
+fn terminate_statement(statement: &str) -> String {
+    let statement = statement.trim();
+    if statement.ends_with(';') {
+        statement.to_string()
+    } else {
+        format!("{};", statement)
+    }
+}
@@ -0,0 +1,131 @@
+use surql_definition_core::AccessDefinition;
+use syn::{Attribute, Error, Result};
+
+use crate::{duration::validate_duration, field::FieldInfo, table_options::DefinitionMode};
+
+/// Record access for a user table, from
+/// `#[surql_access(name = "...", identifier = "...", SESSION = "...")]`.
+#[derive(Clone)]
+pub(crate) struct AccessInfo {
+    pub definition: AccessDefinition,
+    attr: Attribute,
+}
+
+impl AccessInfo {
+    pub(crate) fn parse_attribute(attr: &Attribute) -> Result<Self> {
+        let mut name = None;
+        let mut identifier = None;
+        let mut session = None;
+        let mut token = None;
+        let mut legacy_scope = false;
+
+        attr.parse_nested_meta(|meta| {
+            let attribute_name = meta
+                .path
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+            match attribute_name.as_str() {
+                "SCOPE" => {
+                    legacy_scope = true;
+                    Ok(())
+                }
+                "name" | "identifier" => {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    match attribute_name.as_str() {
+                        "name" => name = Some(lit.value()),
+                        _ => identifier = Some(lit.value()),
+                    }
+                    Ok(())
+                }
+                "SESSION" | "TOKEN" => {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    validate_duration(&lit.value()).map_err(|e| Error::new_spanned(&lit, e))?;
+                    match attribute_name.as_str() {
+                        "SESSION" => session = Some(lit.value()),
+                        _ => token = Some(lit.value()),
+                    }
+                    Ok(())
+                }
+                _ => Err(meta.error("Unrecognized access attribute")),
+            }
+        })?;
+
+        if legacy_scope && token.is_some() {
+            return Err(Error::new_spanned(
+                attr,
+                "TOKEN durations require DEFINE ACCESS",
+            ));
+        }
+
+        Ok(AccessInfo {
+            definition: AccessDefinition {
+                name: name.ok_or_else(|| Error::new_spanned(attr, "Access requires a name"))?,
+                identifier: identifier.ok_or_else(|| {
+                    Error::new_spanned(attr, "Access requires an identifier field to sign in with")
+                })?,
+                password: String::new(),
+                session,
+                token,
+                legacy_scope,
+            },
+            attr: attr.clone(),
+        })
+    }
+
+    /// `DEFINE SCOPE` only exists in SurrealDB 1.x, which has no `OVERWRITE`.
+    pub(crate) fn check_mode(&self, mode: DefinitionMode) -> Result<()> {
+        if self.definition.legacy_scope && mode == DefinitionMode::Overwrite {
+            return Err(Error::new_spanned(
+                &self.attr,
+                "SCOPE can't be combined with OVERWRITE, which requires DEFINE ACCESS",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Ensures the identifier exists and exactly one field is marked as the
+    /// password, which the access then hashes. Both must be fields of the
+    /// struct itself, as flattened fields are only known at runtime.
+    pub(crate) fn resolve_fields(
+        &mut self,
+        fields: &[FieldInfo],
+        has_flattened: bool,
+    ) -> Result<()> {
+        let hint = if has_flattened {
+            ". The identifier and password can't be fields of flattened types"
+        } else {
+            ""
+        };
+
+        let mut passwords = fields.iter().filter(|f| f.is_password());
+        match (passwords.next(), passwords.next()) {
+            (Some(password), None) => self.definition.password = password.name().to_string(),
+            (None, _) => {
+                return Err(Error::new_spanned(
+                    &self.attr,
+                    format!(
+                        "Access requires a field marked with #[surql_field(PASSWORD)]{}",
+                        hint
+                    ),
+                ))
+            }
+            (Some(_), Some(_)) => {
+                return Err(Error::new_spanned(
+                    &self.attr,
+                    "Only one field can be marked as PASSWORD",
+                ))
+            }
+        }
+
+        let identifier = &self.definition.identifier;
+        if !fields.iter().any(|f| f.name() == identifier) {
+            return Err(Error::new_spanned(
+                &self.attr,
+                format!("Unknown identifier field {}{}", identifier, hint),
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+use proc_macro2::Span;
+use surql_definition_core::AnalyzerDefinition;
+use syn::{meta::ParseNestedMeta, Attribute, Error, Expr, ExprArray, Lit, Result};
+
+const TOKENIZERS: [&str; 4] = ["blank", "camel", "class", "punct"];
+
+const SNOWBALL_LANGUAGES: [&str; 17] = [
+    "arabic",
+    "danish",
+    "dutch",
+    "english",
+    "french",
+    "german",
+    "greek",
+    "hungarian",
+    "italian",
+    "norwegian",
+    "portuguese",
+    "romanian",
+    "russian",
+    "spanish",
+    "swedish",
+    "tamil",
+    "turkish",
+];
+
+/// Parses a struct-level `#[surql_analyzer(name = "...", tokenizers = [...], filters = [...])]`.
+pub(crate) fn parse_analyzer_attribute(attr: &Attribute) -> Result<AnalyzerDefinition> {
+    let mut name = None;
+    let mut builder = AnalyzerBuilder::default();
+
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("name") {
+            let lit: syn::LitStr = meta.value()?.parse()?;
+            name = Some(lit.value());
+            Ok(())
+        } else if builder.parse_nested_meta(&meta)? {
+            Ok(())
+        } else {
+            Err(meta.error("Unrecognized analyzer attribute"))
+        }
+    })?;
+
+    let name = name.ok_or_else(|| Error::new_spanned(attr, "Analyzer requires a name"))?;
+    builder
+        .build(name)
+        .ok_or_else(|| Error::new_spanned(attr, "Analyzer requires tokenizers or filters"))
+}
+
+/// Collects the `tokenizers` and `filters` keys shared by `surql_analyzer`
+/// and `surql_search`.
+#[derive(Default)]
+pub(crate) struct AnalyzerBuilder {
+    tokenizers: Vec<String>,
+    filters: Vec<String>,
+}
+
+impl AnalyzerBuilder {
+    /// Returns `false` when the key isn't an analyzer setting.
+    pub(crate) fn parse_nested_meta(&mut self, meta: &ParseNestedMeta) -> Result<bool> {
+        if meta.path.is_ident("tokenizers") {
+            for (tokenizer, span) in parse_string_array(meta)? {
+                self.tokenizers
+                    .push(validate_tokenizer(&tokenizer).map_err(|e| Error::new(span, e))?);
+            }
+            Ok(true)
+        } else if meta.path.is_ident("filters") {
+            for (filter, span) in parse_string_array(meta)? {
+                self.filters
+                    .push(validate_filter(&filter).map_err(|e| Error::new(span, e))?);
+            }
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub(crate) fn build(self, name: String) -> Option<AnalyzerDefinition> {
+        if self.tokenizers.is_empty() && self.filters.is_empty() {
+            None
+        } else {
+            Some(AnalyzerDefinition {
+                name,
+                tokenizers: self.tokenizers,
+                filters: self.filters,
+            })
+        }
+    }
+}
+
+fn parse_string_array(meta: &ParseNestedMeta) -> Result<Vec<(String, Span)>> {
+    let array: ExprArray = meta.value()?.parse()?;
+    array
+        .elems
+        .iter()
+        .map(|elem| match elem {
+            Expr::Lit(expr_lit) => match &expr_lit.lit {
+                Lit::Str(lit_str) => Ok((lit_str.value(), lit_str.span())),
+                _ => Err(Error::new_spanned(elem, "Expected a string")),
+            },
+            _ => Err(Error::new_spanned(elem, "Expected a string")),
+        })
+        .collect()
+}
+
+/// Validates a tokenizer such as `blank` and returns its normalized form.
+fn validate_tokenizer(tokenizer: &str) -> std::result::Result<String, String> {
+    let tokenizer = tokenizer.trim().to_ascii_lowercase();
+    if TOKENIZERS.contains(&tokenizer.as_str()) {
+        Ok(tokenizer)
+    } else {
+        Err(format!(
+            "Unknown tokenizer {}, expected one of {}",
+            tokenizer,
+            TOKENIZERS.join(", ")
+        ))
+    }
+}
+
+/// Validates a filter such as `lowercase`, `edgengram(2,10)` or
+/// `snowball(english)` and returns its normalized form.
+fn validate_filter(filter: &str) -> std::result::Result<String, String> {
+    let filter = filter.trim();
+    let (name, args) = match filter.split_once('(') {
+        Some((name, rest)) => {
+            let args = rest
+                .strip_suffix(')')
+                .ok_or_else(|| format!("Unclosed parenthesis in filter {}", filter))?;
+            (name.trim().to_ascii_lowercase(), Some(args))
+        }
+        None => (filter.to_ascii_lowercase(), None),
+    };
+
+    match (name.as_str(), args) {
+        ("ascii" | "lowercase" | "uppercase", None) => Ok(name),
+        ("edgengram" | "ngram", Some(args)) => {
+            let bounds = args
+                .split(',')
+                .map(|arg| arg.trim().parse::<u32>())
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|_| format!("{} expects two integers", name))?;
+            match bounds.as_slice() {
+                [min, max] if min <= max => Ok(format!("{}({},{})", name, min, max)),
+                [_, _] => Err(format!("{} minimum must not exceed its maximum", name)),
+                _ => Err(format!("{} expects two integers", name)),
+            }
+        }
+        ("snowball", Some(language)) => {
+            let language = language.trim().to_ascii_lowercase();
+            if SNOWBALL_LANGUAGES.contains(&language.as_str()) {
+                Ok(format!("snowball({})", language))
+            } else {
+                Err(format!("Unsupported snowball language {}", language))
+            }
+        }
+        ("mapper", Some(path)) => {
+            let path = path.trim().trim_matches(|c| c == '\'' || c == '"');
+            Ok(format!("mapper('{}')", path))
+        }
+        ("ascii" | "lowercase" | "uppercase", Some(_)) => {
+            Err(format!("{} does not take arguments", name))
+        }
+        ("edgengram" | "ngram" | "snowball" | "mapper", None) => {
+            Err(format!("{} requires arguments", name))
+        }
+        _ => Err(format!(
+            "Unknown filter {}, expected one of ascii, lowercase, uppercase, edgengram, ngram, snowball, mapper",
+            name
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_filter, validate_tokenizer};
+
+    #[test]
+    fn normalizes_tokenizers() {
+        assert_eq!(validate_tokenizer("Blank"), Ok("blank".to_string()));
+    }
+
+    #[test]
+    fn rejects_unknown_tokenizers() {
+        assert_eq!(
+            validate_tokenizer("space"),
+            Err("Unknown tokenizer space, expected one of blank, camel, class, punct".to_string())
+        );
+    }
+
+    #[test]
+    fn normalizes_filters() {
+        assert_eq!(validate_filter("Lowercase"), Ok("lowercase".to_string()));
+        assert_eq!(
+            validate_filter("edgengram(2, 10)"),
+            Ok("edgengram(2,10)".to_string())
+        );
+        assert_eq!(
+            validate_filter("snowball(English)"),
+            Ok("snowball(english)".to_string())
+        );
+        assert_eq!(
+            validate_filter("mapper(\"words.txt\")"),
+            Ok("mapper('words.txt')".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_filters() {
+        assert_eq!(
+            validate_filter("stem"),
+            Err("Unknown filter stem, expected one of ascii, lowercase, uppercase, edgengram, ngram, snowball, mapper".to_string())
+        );
+        assert_eq!(
+            validate_filter("snowball(klingon)"),
+            Err("Unsupported snowball language klingon".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_filter_arguments() {
+        assert_eq!(
+            validate_filter("ngram(2,10"),
+            Err("Unclosed parenthesis in filter ngram(2,10".to_string())
+        );
+        assert_eq!(
+            validate_filter("ngram(a,b)"),
+            Err("ngram expects two integers".to_string())
+        );
+        assert_eq!(
+            validate_filter("ngram(1)"),
+            Err("ngram expects two integers".to_string())
+        );
+        assert_eq!(
+            validate_filter("edgengram(10,2)"),
+            Err("edgengram minimum must not exceed its maximum".to_string())
+        );
+        assert_eq!(
+            validate_filter("lowercase(1)"),
+            Err("lowercase does not take arguments".to_string())
+        );
+        assert_eq!(
+            validate_filter("snowball"),
+            Err("snowball requires arguments".to_string())
+        );
+    }
+}
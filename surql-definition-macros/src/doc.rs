@@ -0,0 +1,33 @@
+use syn::{Attribute, Expr, Lit, Meta};
+
+/// Collects the `///` doc comments of an item into a single string, one line
+/// per comment line.
+pub(crate) fn parse_doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(name_value) => match &name_value.value {
+                Expr::Lit(expr_lit) => match &expr_lit.lit {
+                    Lit::Str(lit_str) => Some(lit_str.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| {
+            line.strip_prefix(' ')
+                .unwrap_or(&line)
+                .trim_end()
+                .to_string()
+        })
+        .collect();
+
+    let comment = lines.join("\n").trim().to_string();
+    if comment.is_empty() {
+        None
+    } else {
+        Some(comment)
+    }
+}
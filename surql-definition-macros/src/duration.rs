@@ -0,0 +1,94 @@
+const DURATION_UNITS: [&str; 10] = ["ns", "us", "µs", "ms", "s", "m", "h", "d", "w", "y"];
+
+/// Checks that `value` is a SurrealQL duration such as `3d`, `1h30m` or `500ms`.
+pub(crate) fn validate_duration(value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Err("Duration must not be empty".to_string());
+    }
+
+    let mut rest = value;
+    while !rest.is_empty() {
+        let digits = rest.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(format!(
+                "Invalid duration `{}`: expected a number before `{}`",
+                value, rest
+            ));
+        }
+        let number = &rest[..digits];
+        rest = &rest[digits..];
+
+        let unit_len = rest
+            .char_indices()
+            .find(|(_, c)| c.is_ascii_digit())
+            .map_or(rest.len(), |(index, _)| index);
+        let unit = &rest[..unit_len];
+        if unit.is_empty() {
+            return Err(format!(
+                "Invalid duration `{}`: missing unit after `{}`, expected one of ns, us, µs, ms, s, m, h, d, w, y",
+                value, number
+            ));
+        }
+        if !DURATION_UNITS.contains(&unit) {
+            return Err(format!(
+                "Invalid duration `{}`: unknown unit `{}`, expected one of ns, us, µs, ms, s, m, h, d, w, y",
+                value, unit
+            ));
+        }
+        rest = &rest[unit_len..];
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_duration;
+
+    #[test]
+    fn accepts_compound_durations() {
+        for value in ["3d", "1h30m", "500ms", "10µs", "1y2w"] {
+            assert_eq!(validate_duration(value), Ok(()), "{}", value);
+        }
+    }
+
+    #[test]
+    fn rejects_empty_durations() {
+        assert_eq!(
+            validate_duration(""),
+            Err("Duration must not be empty".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_missing_numbers() {
+        assert_eq!(
+            validate_duration("d"),
+            Err("Invalid duration `d`: expected a number before `d`".to_string())
+        );
+        assert_eq!(
+            validate_duration("-1h"),
+            Err("Invalid duration `-1h`: expected a number before `-1h`".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_units() {
+        assert_eq!(
+            validate_duration("5x"),
+            Err("Invalid duration `5x`: unknown unit `x`, expected one of ns, us, µs, ms, s, m, h, d, w, y".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_missing_units() {
+        assert_eq!(
+            validate_duration("10"),
+            Err("Invalid duration `10`: missing unit after `10`, expected one of ns, us, µs, ms, s, m, h, d, w, y".to_string())
+        );
+        assert_eq!(
+            validate_duration("1h30"),
+            Err("Invalid duration `1h30`: missing unit after `30`, expected one of ns, us, µs, ms, s, m, h, d, w, y".to_string())
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use surql_definition_core::EventDefinition;
+use syn::{Attribute, Error, Result};
+
+/// Parses a struct-level `#[surql_event(name = "...", WHEN = "...", THEN = "...")]`.
+pub(crate) fn parse_event_attribute(attr: &Attribute) -> Result<EventDefinition> {
+    let mut name = None;
+    let mut when = None;
+    let mut then = None;
+
+    attr.parse_nested_meta(|meta| {
+        let attribute_name = meta
+            .path
+            .get_ident()
+            .map(|ident| ident.to_string())
+            .unwrap_or_default();
+        let target = match attribute_name.as_str() {
+            "name" => &mut name,
+            "WHEN" => &mut when,
+            "THEN" => &mut then,
+            _ => return Err(meta.error("Unrecognized event attribute")),
+        };
+        let lit: syn::LitStr = meta.value()?.parse()?;
+        *target = Some(lit.value());
+        Ok(())
+    })?;
+
+    let name = name.ok_or_else(|| Error::new_spanned(attr, "Event requires a name"))?;
+    let then = then.ok_or_else(|| Error::new_spanned(attr, "Event requires a THEN clause"))?;
+
+    Ok(EventDefinition { name, when, then })
+}
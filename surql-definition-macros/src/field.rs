@@ -1,8 +1,13 @@
 use crate::{
-    permission::{parse_permissions_attributes, PermissionInfo},
-    type_conv::{format_lit_as_expr, SurrealDBType},
+    doc::parse_doc_comment,
+    index::{IndexKind, SearchInfo, VectorInfo},
+    permission::PermissionSet,
+    rename::RenameRule,
+    serde_attr::{skip_meta, SerdeDefault, SerdeField},
+    type_conv::{format_lit_as_expr, is_char, SurrealDBType},
 };
-use syn::{Data, DeriveInput, Error, Field, Lit, Result};
+use surql_definition_core::{FieldDefinition, PermissionDefinition};
+use syn::{ext::IdentExt, Data, DeriveInput, Error, Field, Lit, LitStr, Result, Type};
 
 #[derive(Clone)]
 pub(crate) struct FieldInfo {
@@ -13,7 +18,10 @@ pub(crate) struct FieldInfo {
     readonly: bool,
     value: Option<String>,
     assertion: Option<String>,
-    permissions: Vec<PermissionInfo>,
+    permissions: Option<PermissionDefinition>,
+    comment: Option<String>,
+    indexes: Vec<IndexKind>,
+    password: bool,
 }
 
 impl FieldInfo {
@@ -26,77 +34,75 @@ impl FieldInfo {
             readonly: false,
             value: None,
             assertion: None,
-            permissions: vec![],
+            permissions: None,
+            comment: None,
+            indexes: vec![],
+            password: false,
         }
     }
 
-    pub(crate) fn generate_define_query(&self, table_name: &str) -> String {
-        let mut define_field = format!("DEFINE FIELD {} ON {}", self.name, table_name);
-
-        if self.type_is_flexible {
-            define_field.push_str(" FLEXIBLE");
-        }
-
-        if let Some(field_ty) = &self.field_type {
-            if !field_ty.to_string().is_empty() {
-                define_field.push_str(&format!(" TYPE {}", field_ty.to_string()));
-            }
-        }
-
-        if let Some(default) = &self.default {
-            define_field.push_str(&format!(" DEFAULT {}", default));
-        }
-        if let Some(value) = &self.value {
-            define_field.push_str(&format!(" VALUE {}", value));
-        }
-
-        if self.readonly {
-            define_field.push_str(" READONLY");
-        }
-        if let Some(assertion) = &self.assertion {
-            define_field.push_str(&format!(" ASSERT {}", assertion));
-        }
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
 
-        let permissions_str = self
-            .permissions
-            .iter()
-            .map(ToString::to_string)
-            .collect::<Vec<String>>();
+    pub(crate) fn is_password(&self) -> bool {
+        self.password
+    }
 
-        if !self.permissions.is_empty() {
-            define_field.push_str(" PERMISSIONS ");
-            define_field.push_str(&permissions_str.join(" "));
-        }
+    pub(crate) fn indexes(&self) -> &[IndexKind] {
+        &self.indexes
+    }
 
-        if !define_field.ends_with(";") {
-            define_field.push_str(";");
+    pub(crate) fn to_definition(&self) -> FieldDefinition {
+        FieldDefinition {
+            name: self.name.clone(),
+            flexible: self.type_is_flexible,
+            field_type: self.field_type.as_ref().map(ToString::to_string),
+            default: self.default.clone(),
+            value: self.value.clone(),
+            readonly: self.readonly,
+            assertion: self.assertion.clone(),
+            permissions: self.permissions.clone(),
+            comment: self.comment.clone(),
         }
-
-        define_field
     }
 
-    pub(crate) fn from_field(f: &Field) -> Result<Self> {
-        let name = f
+    /// Parses a struct field. The database name follows serde's `rename` and
+    /// `rename_all` unless `#[surql_field(RENAME = "...")]` is set.
+    pub(crate) fn from_field(f: &Field, context: &FieldContext) -> Result<Self> {
+        let ident = f
             .ident
             .as_ref()
             .ok_or_else(|| Error::new_spanned(f, "Expected field to have an identifier"))?
+            .unraw()
             .to_string();
+        let serde_field = SerdeField::from_attrs(&f.attrs)?;
+        let name = serde_field
+            .rename
+            .or_else(|| context.rename_all.map(|rule| rule.apply_to_field(&ident)))
+            .unwrap_or(ident);
 
         let mut field_info = FieldInfo::new(name);
+        field_info.comment = parse_doc_comment(&f.attrs);
+        let mut perms = PermissionSet::default();
 
         for attr in &f.attrs {
             if attr.path().is_ident("surql_field") {
                 field_info = field_info.parse_field_attributes(attr)?;
+            } else if attr.path().is_ident("surql_search") {
+                let search = SearchInfo::parse_attribute(attr)?;
+                field_info.indexes.push(IndexKind::Search(search));
+            } else if attr.path().is_ident("surql_vector") {
+                let vector = VectorInfo::parse_attribute(attr)?;
+                field_info.indexes.push(IndexKind::Vector(vector));
             } else if attr.path().is_ident("surql_field_permissions") {
                 attr.parse_nested_meta(|meta| {
-                    let perm = parse_permissions_attributes(meta).map_err(|e| {
+                    perms.parse_nested_meta(meta).map_err(|e| {
                         Error::new_spanned(
                             attr,
                             format!("Failed to parse permissions attribute: {}", e),
                         )
-                    })?;
-                    field_info.permissions.push(perm);
-                    Ok(())
+                    })
                 })
                 .map_err(|e| {
                     Error::new_spanned(
@@ -107,11 +113,79 @@ impl FieldInfo {
             }
         }
 
+        field_info.permissions = perms.build().map_err(|e| Error::new_spanned(f, e))?;
+
         // Infer the type if not explicitly set
         if field_info.field_type.is_none() {
             field_info.field_type = Some(SurrealDBType::from_type(&f.ty)?);
         }
 
+        if context.serde_defaults && field_info.default.is_none() {
+            match serde_field.default.as_ref().or(context.default.as_ref()) {
+                Some(SerdeDefault::Type) => {
+                    // `char` defaults to '\0' rather than an empty string
+                    let default = field_info
+                        .field_type
+                        .as_ref()
+                        .filter(|_| !is_char(&f.ty))
+                        .and_then(SurrealDBType::default_literal)
+                        .ok_or_else(|| {
+                            Error::new_spanned(
+                                &f.ty,
+                                "Unable to infer the default value of this type, set DEFAULT explicitly",
+                            )
+                        })?;
+                    field_info.default = Some(default);
+                }
+                Some(SerdeDefault::Custom { description, span }) => {
+                    return Err(Error::new(
+                        *span,
+                        format!(
+                            "The default of {} comes from {}, which can't be evaluated at compile time; set DEFAULT explicitly",
+                            field_info.name, description
+                        ),
+                    ));
+                }
+                None => {}
+            }
+        }
+
+        let field_type = field_info.field_type.as_ref();
+        for kind in &mut field_info.indexes {
+            match kind {
+                IndexKind::Search(search)
+                    if !field_type.is_some_and(SurrealDBType::is_string_like) =>
+                {
+                    return Err(Error::new(
+                        search.span,
+                        "Search indexes can only be defined on string fields",
+                    ));
+                }
+                IndexKind::Vector(vector) => {
+                    if !field_type
+                        .and_then(SurrealDBType::array_element)
+                        .is_some_and(SurrealDBType::is_numeric)
+                    {
+                        return Err(Error::new(
+                            vector.span,
+                            "Vector indexes can only be defined on arrays of numbers",
+                        ));
+                    }
+                    // Fixed-size arrays carry their dimension
+                    if vector.index.dimension.is_none() {
+                        vector.index.dimension = field_type.and_then(SurrealDBType::array_len);
+                    }
+                    if vector.index.dimension.is_none() {
+                        return Err(Error::new(
+                            vector.span,
+                            "Unable to infer the vector dimension, set DIMENSION explicitly",
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
         Ok(field_info)
     }
 
@@ -133,6 +207,26 @@ impl FieldInfo {
                     field_info.readonly = true;
                     Ok(())
                 }
+                "PASSWORD" => {
+                    field_info.password = true;
+                    Ok(())
+                }
+                "SKIP" | "FLATTEN" => Ok(()),
+                "RENAME" => {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    field_info.name = lit.value();
+                    Ok(())
+                }
+                "INDEX" | "UNIQUE" => {
+                    field_info
+                        .indexes
+                        .retain(|kind| !matches!(kind, IndexKind::Standard | IndexKind::Unique));
+                    field_info.indexes.push(match attribute_name.as_str() {
+                        "INDEX" => IndexKind::Standard,
+                        _ => IndexKind::Unique,
+                    });
+                    Ok(())
+                }
                 "TYPE" => {
                     let lit: Lit = meta.value()?.parse()?;
                     match lit {
@@ -164,15 +258,97 @@ impl FieldInfo {
         Ok(field_info)
     }
 
-    pub fn parse_fields(input: &DeriveInput) -> Result<Vec<Self>> {
-        if let Data::Struct(data_struct) = &input.data {
-            data_struct
-                .fields
-                .iter()
-                .map(|f| FieldInfo::from_field(f))
-                .collect::<Result<Vec<_>>>()
-        } else {
-            Err(Error::new_spanned(input, "Unsupported data type"))
+    /// Whether the field is left out of the schema: marked with
+    /// `#[surql_field(SKIP)]`, never serialized by serde, or a `PhantomData`.
+    pub(crate) fn is_skipped(f: &Field) -> Result<bool> {
+        if SerdeField::from_attrs(&f.attrs)?.skip {
+            return Ok(true);
+        }
+        if let Type::Path(type_path) = &f.ty {
+            if type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "PhantomData")
+            {
+                return Ok(true);
+            }
+        }
+
+        has_field_flag(f, "SKIP")
+    }
+
+    /// Whether the field is inlined from its type, with `#[serde(flatten)]`
+    /// or `#[surql_field(FLATTEN)]`.
+    pub(crate) fn is_flattened(f: &Field) -> Result<bool> {
+        Ok(SerdeField::from_attrs(&f.attrs)?.flatten || has_field_flag(f, "FLATTEN")?)
+    }
+
+    /// Parses the struct fields, leaving out skipped and `excluded` ones.
+    /// Flattened fields are returned apart with the position they take among
+    /// the regular fields.
+    pub fn parse_fields(
+        input: &DeriveInput,
+        excluded: &[&str],
+        context: &FieldContext,
+    ) -> Result<(Vec<Self>, Vec<FlattenedField>)> {
+        let Data::Struct(data_struct) = &input.data else {
+            return Err(Error::new_spanned(input, "Unsupported data type"));
+        };
+
+        let mut fields = vec![];
+        let mut flattened = vec![];
+        for f in &data_struct.fields {
+            if f.ident
+                .as_ref()
+                .is_some_and(|ident| excluded.contains(&ident.unraw().to_string().as_str()))
+                || FieldInfo::is_skipped(f)?
+            {
+                continue;
+            }
+
+            if FieldInfo::is_flattened(f)? {
+                flattened.push(FlattenedField {
+                    position: fields.len(),
+                    ty: f.ty.clone(),
+                });
+            } else {
+                fields.push(FieldInfo::from_field(f, context)?);
+            }
         }
+
+        Ok((fields, flattened))
+    }
+}
+
+/// Struct-level settings applied to every field.
+#[derive(Default)]
+pub(crate) struct FieldContext {
+    pub rename_all: Option<RenameRule>,
+    /// Set by `SERDE_DEFAULTS`: serde defaults become `DEFAULT` clauses.
+    pub serde_defaults: bool,
+    /// The default of fields missing from `#[serde(default)]` structs.
+    pub default: Option<SerdeDefault>,
+}
+
+/// A field whose definitions are inlined from the `SurQLSchemaProducer`
+/// implementation of its type.
+pub(crate) struct FlattenedField {
+    pub position: usize,
+    pub ty: Type,
+}
+
+fn has_field_flag(f: &Field, flag: &str) -> Result<bool> {
+    let mut found = false;
+    for attr in f.attrs.iter().filter(|a| a.path().is_ident("surql_field")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(flag) {
+                found = true;
+                Ok(())
+            } else {
+                skip_meta(&meta)
+            }
+        })?;
     }
+    Ok(found)
 }
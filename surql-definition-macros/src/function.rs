@@ -0,0 +1,138 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{
+    ext::IdentExt, meta::ParseNestedMeta, Error, FnArg, ForeignItemFn, Ident, LitStr, Pat, Result,
+    ReturnType,
+};
+
+use crate::{
+    model,
+    table_options::{parse_definition_mode, DefinitionMode},
+    type_conv::SurrealDBType,
+};
+
+/// A `DEFINE FUNCTION` generated from a bodiless Rust signature with
+/// `#[surql_function(body = "...")]`.
+#[derive(Default)]
+pub(crate) struct FunctionInfo {
+    name: Option<String>,
+    body: Option<LitStr>,
+    mode: DefinitionMode,
+}
+
+impl FunctionInfo {
+    /// Parses the `body`, `name`, `IF_NOT_EXISTS` and `OVERWRITE` arguments.
+    pub(crate) fn parse_nested_meta(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("body") {
+            self.body = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("name") {
+            let lit: LitStr = meta.value()?.parse()?;
+            self.name = Some(lit.value().trim_start_matches("fn::").to_string());
+            Ok(())
+        } else if parse_definition_mode(&mut self.mode, &meta)? {
+            Ok(())
+        } else {
+            Err(meta.error("Unrecognized function attribute"))
+        }
+    }
+
+    fn arguments(item: &ForeignItemFn) -> Result<Vec<(&Ident, SurrealDBType)>> {
+        item.sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                FnArg::Typed(arg) => match &*arg.pat {
+                    Pat::Ident(pat) => Ok((&pat.ident, SurrealDBType::from_type(&arg.ty)?)),
+                    _ => Err(Error::new_spanned(
+                        &arg.pat,
+                        "Function arguments must be plain identifiers",
+                    )),
+                },
+                FnArg::Receiver(receiver) => {
+                    Err(Error::new_spanned(receiver, "Functions can't take self"))
+                }
+            })
+            .collect()
+    }
+
+    fn function_name(&self, item: &ForeignItemFn) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| item.sig.ident.unraw().to_string())
+    }
+
+    pub(crate) fn generate_define_query(&self, item: &ForeignItemFn) -> Result<String> {
+        if !item.sig.generics.params.is_empty() {
+            return Err(Error::new_spanned(
+                &item.sig.generics,
+                "Generic functions are not supported",
+            ));
+        }
+        let body = self
+            .body
+            .as_ref()
+            .ok_or_else(|| Error::new_spanned(&item.sig, "Function requires a body"))?
+            .value();
+
+        let arguments = Self::arguments(item)?
+            .into_iter()
+            .map(|(ident, ty)| format!("${}: {}", ident.unraw(), ty))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut query = format!(
+            "{} fn::{}({})",
+            self.mode.define("FUNCTION"),
+            self.function_name(item),
+            arguments
+        );
+        if let ReturnType::Type(_, ty) = &item.sig.output {
+            query.push_str(&format!(" -> {}", SurrealDBType::from_type(ty)?));
+        }
+
+        let body = body.trim();
+        if body.starts_with('{') {
+            query.push_str(&format!(" {};", body));
+        } else {
+            query.push_str(&format!(" {{ {} }};", body));
+        }
+
+        Ok(query)
+    }
+
+    /// Adds a `<NAME>_SURQL` const holding the definition and a helper with
+    /// the same arguments returning the `fn::name(...)` call expression. The
+    /// declared return type only types the `DEFINE FUNCTION`, as the helper
+    /// returns the call expression.
+    pub(crate) fn expand(&self, item: &ForeignItemFn, query: &str) -> Result<TokenStream> {
+        let krate = model::crate_path();
+        let vis = &item.vis;
+        let attrs = &item.attrs;
+        let ident = &item.sig.ident;
+        let inputs = &item.sig.inputs;
+        let surql_ident = format_ident!("{}_SURQL", ident.unraw().to_string().to_uppercase());
+        let doc = format!(
+            "SurrealQL definition of `fn::{}`.",
+            self.function_name(item)
+        );
+        let call = format!("fn::{}({{}})", self.function_name(item));
+        let arguments = Self::arguments(item)?
+            .into_iter()
+            .map(|(ident, _)| ident)
+            .collect::<Vec<_>>();
+
+        Ok(quote! {
+            #[doc = #doc]
+            #vis const #surql_ident: &'static str = #query;
+
+            #(#attrs)*
+            #vis fn #ident(#inputs) -> String {
+                let arguments: Vec<String> = vec![
+                    #(#krate::ToSurQLLiteral::to_surql_literal(&#arguments)),*
+                ];
+                format!(#call, arguments.join(", "))
+            }
+        })
+    }
+}
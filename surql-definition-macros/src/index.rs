@@ -0,0 +1,357 @@
+use proc_macro2::Span;
+use surql_definition_core::{
+    AnalyzerDefinition, Bm25, IndexDefinition, SearchIndex, VectorAlgorithm, VectorIndex,
+};
+use syn::{punctuated::Punctuated, Attribute, Error, Expr, ExprArray, Lit, Result, Token};
+
+use crate::analyzer::AnalyzerBuilder;
+
+#[derive(Clone)]
+pub(crate) struct SearchInfo {
+    pub index: SearchIndex,
+    /// Analyzer declared inline with `tokenizers`/`filters`.
+    pub inline_analyzer: Option<AnalyzerDefinition>,
+    pub span: Span,
+}
+
+impl SearchInfo {
+    /// Parses a field-level `#[surql_search(analyzer = "...", BM25, HIGHLIGHTS)]`.
+    pub(crate) fn parse_attribute(attr: &Attribute) -> Result<Self> {
+        let mut analyzer = None;
+        let mut bm25 = None;
+        let mut highlights = false;
+        let mut builder = AnalyzerBuilder::default();
+
+        attr.parse_nested_meta(|meta| {
+            let attribute_name = meta
+                .path
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+            match attribute_name.as_str() {
+                "analyzer" => {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    analyzer = Some(lit.value());
+                    Ok(())
+                }
+                "BM25" => {
+                    if meta.input.peek(syn::token::Paren) {
+                        let content;
+                        syn::parenthesized!(content in meta.input);
+                        let params = Punctuated::<Lit, Token![,]>::parse_terminated(&content)?;
+                        let params: Vec<String> = params
+                            .iter()
+                            .map(|lit| match lit {
+                                Lit::Float(lit) => Ok(lit.base10_digits().to_string()),
+                                Lit::Int(lit) => Ok(lit.base10_digits().to_string()),
+                                _ => Err(Error::new_spanned(lit, "Expected a number")),
+                            })
+                            .collect::<Result<_>>()?;
+                        match params.as_slice() {
+                            [k1, b] => {
+                                bm25 = Some(Bm25::Parameters {
+                                    k1: k1.clone(),
+                                    b: b.clone(),
+                                })
+                            }
+                            _ => return Err(meta.error("BM25 expects the k1 and b parameters")),
+                        }
+                    } else {
+                        bm25 = Some(Bm25::Default);
+                    }
+                    Ok(())
+                }
+                "HIGHLIGHTS" => {
+                    highlights = true;
+                    Ok(())
+                }
+                _ if builder.parse_nested_meta(&meta)? => Ok(()),
+                _ => Err(meta.error("Unrecognized search attribute")),
+            }
+        })?;
+
+        let analyzer = analyzer
+            .ok_or_else(|| Error::new_spanned(attr, "Search index requires an analyzer"))?;
+
+        Ok(SearchInfo {
+            inline_analyzer: builder.build(analyzer.clone()),
+            index: SearchIndex {
+                analyzer,
+                bm25,
+                highlights,
+            },
+            span: attr.pound_token.span,
+        })
+    }
+}
+
+const DISTANCES: [&str; 7] = [
+    "EUCLIDEAN",
+    "COSINE",
+    "MANHATTAN",
+    "CHEBYSHEV",
+    "HAMMING",
+    "JACCARD",
+    "PEARSON",
+];
+
+const MTREE_DISTANCES: [&str; 3] = ["EUCLIDEAN", "COSINE", "MANHATTAN"];
+
+const VECTOR_TYPES: [&str; 5] = ["F64", "F32", "I64", "I32", "I16"];
+
+#[derive(Clone)]
+pub(crate) struct VectorInfo {
+    pub index: VectorIndex,
+    pub span: Span,
+}
+
+impl VectorInfo {
+    /// Parses a field-level `#[surql_vector(MTREE, DIMENSION = 3, DIST = "COSINE")]`
+    /// or `#[surql_vector(HNSW, DIST = "EUCLIDEAN", EFC = 150, M = 12)]`.
+    pub(crate) fn parse_attribute(attr: &Attribute) -> Result<Self> {
+        let mut algorithm = None;
+        let mut vector = VectorIndex {
+            algorithm: VectorAlgorithm::Mtree,
+            dimension: None,
+            vector_type: None,
+            distance: None,
+            capacity: None,
+            efc: None,
+            m: None,
+        };
+
+        attr.parse_nested_meta(|meta| {
+            let attribute_name = meta
+                .path
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+            match attribute_name.as_str() {
+                "MTREE" | "HNSW" => {
+                    if algorithm.is_some() {
+                        return Err(meta.error("MTREE and HNSW are mutually exclusive"));
+                    }
+                    algorithm = Some(match attribute_name.as_str() {
+                        "MTREE" => VectorAlgorithm::Mtree,
+                        _ => VectorAlgorithm::Hnsw,
+                    });
+                    Ok(())
+                }
+                "DIST" | "TYPE" => {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    let value = lit.value().to_ascii_uppercase();
+                    let (allowed, target): (&[&str], _) = match attribute_name.as_str() {
+                        "DIST" => (&DISTANCES, &mut vector.distance),
+                        _ => (&VECTOR_TYPES, &mut vector.vector_type),
+                    };
+                    if !allowed.contains(&value.as_str()) {
+                        return Err(Error::new_spanned(
+                            lit,
+                            format!("Expected one of {}", allowed.join(", ")),
+                        ));
+                    }
+                    *target = Some(value);
+                    Ok(())
+                }
+                "DIMENSION" | "CAPACITY" | "EFC" | "M" => {
+                    let lit: syn::LitInt = meta.value()?.parse()?;
+                    let value = Some(lit.base10_parse::<usize>()?);
+                    match attribute_name.as_str() {
+                        "DIMENSION" => vector.dimension = value,
+                        "CAPACITY" => vector.capacity = value,
+                        "EFC" => vector.efc = value,
+                        "M" => vector.m = value,
+                        _ => unreachable!(),
+                    }
+                    Ok(())
+                }
+                _ => Err(meta.error("Unrecognized vector index attribute")),
+            }
+        })?;
+
+        vector.algorithm = algorithm
+            .ok_or_else(|| Error::new_spanned(attr, "Vector index requires MTREE or HNSW"))?;
+
+        match vector.algorithm {
+            VectorAlgorithm::Mtree => {
+                if vector.efc.is_some() || vector.m.is_some() {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "EFC and M only apply to HNSW indexes",
+                    ));
+                }
+                if let Some(distance) = &vector.distance {
+                    if !MTREE_DISTANCES.contains(&distance.as_str()) {
+                        return Err(Error::new_spanned(
+                            attr,
+                            format!(
+                                "MTREE indexes support the {} distances",
+                                MTREE_DISTANCES.join(", ")
+                            ),
+                        ));
+                    }
+                }
+            }
+            VectorAlgorithm::Hnsw => {
+                if vector.capacity.is_some() {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "CAPACITY only applies to MTREE indexes",
+                    ));
+                }
+            }
+        }
+
+        Ok(VectorInfo {
+            index: vector,
+            span: attr.pound_token.span,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum IndexKind {
+    Standard,
+    Unique,
+    Search(SearchInfo),
+    Vector(VectorInfo),
+}
+
+impl IndexKind {
+    fn suffix(&self) -> &'static str {
+        match self {
+            IndexKind::Standard | IndexKind::Unique => "idx",
+            IndexKind::Search(_) => "search",
+            IndexKind::Vector(_) => "vector",
+        }
+    }
+
+    fn to_definition(&self) -> surql_definition_core::IndexKind {
+        match self {
+            IndexKind::Standard => surql_definition_core::IndexKind::Standard,
+            IndexKind::Unique => surql_definition_core::IndexKind::Unique,
+            IndexKind::Search(search) => {
+                surql_definition_core::IndexKind::Search(search.index.clone())
+            }
+            IndexKind::Vector(vector) => {
+                surql_definition_core::IndexKind::Vector(vector.index.clone())
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct IndexInfo {
+    name: String,
+    fields: Vec<String>,
+    kind: IndexKind,
+    span: Span,
+}
+
+impl IndexInfo {
+    /// Index generated from a field attribute, such as the `INDEX`/`UNIQUE`
+    /// shortcuts of `surql_field` or `surql_search`.
+    pub(crate) fn for_field(table_name: &str, field_name: &str, kind: IndexKind) -> Self {
+        IndexInfo {
+            name: format!("{}_{}_{}", table_name, field_name, kind.suffix()),
+            fields: vec![field_name.to_string()],
+            kind,
+            span: Span::call_site(),
+        }
+    }
+
+    /// Parses a struct-level `#[surql_index(name = "...", fields = [...], UNIQUE)]`.
+    pub(crate) fn parse_attribute(attr: &Attribute) -> Result<Self> {
+        let mut name = None;
+        let mut fields = vec![];
+        let mut kind = IndexKind::Standard;
+
+        attr.parse_nested_meta(|meta| {
+            let attribute_name = meta
+                .path
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+            match attribute_name.as_str() {
+                "name" => {
+                    let lit: syn::LitStr = meta.value()?.parse()?;
+                    name = Some(lit.value());
+                    Ok(())
+                }
+                "fields" => {
+                    let array: ExprArray = meta.value()?.parse()?;
+                    for elem in &array.elems {
+                        fields.push(parse_field_name(elem)?);
+                    }
+                    Ok(())
+                }
+                "UNIQUE" => {
+                    kind = IndexKind::Unique;
+                    Ok(())
+                }
+                _ => Err(meta.error("Unrecognized index attribute")),
+            }
+        })?;
+
+        let name = name.ok_or_else(|| Error::new_spanned(attr, "Index requires a name"))?;
+        if fields.is_empty() {
+            return Err(Error::new_spanned(
+                attr,
+                "Index requires at least one field",
+            ));
+        }
+
+        Ok(IndexInfo {
+            name,
+            fields,
+            kind,
+            span: attr.pound_token.span,
+        })
+    }
+
+    /// Ensures every indexed field exists on the struct. Nested paths such as
+    /// `address.city` are checked on their first segment.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn span(&self) -> Span {
+        self.span
+    }
+
+    pub(crate) fn check_fields(&self, known_fields: &[&str]) -> Result<()> {
+        for field in &self.fields {
+            let root = field.split(['.', '[']).next().unwrap_or_default();
+            if !known_fields.contains(&root) {
+                return Err(Error::new(
+                    self.span,
+                    format!("Index {} references unknown field {}", self.name, field),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn to_definition(&self) -> IndexDefinition {
+        IndexDefinition {
+            name: self.name.clone(),
+            fields: self.fields.clone(),
+            kind: self.kind.to_definition(),
+        }
+    }
+}
+
+fn parse_field_name(expr: &Expr) -> Result<String> {
+    match expr {
+        Expr::Lit(expr_lit) => match &expr_lit.lit {
+            Lit::Str(lit_str) => Ok(lit_str.value()),
+            _ => Err(Error::new_spanned(expr, "Expected a field name")),
+        },
+        Expr::Path(expr_path) => expr_path
+            .path
+            .get_ident()
+            .map(ToString::to_string)
+            .ok_or_else(|| Error::new_spanned(expr, "Expected a field name")),
+        _ => Err(Error::new_spanned(expr, "Expected a field name")),
+    }
+}
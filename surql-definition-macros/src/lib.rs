@@ -1,20 +1,49 @@
+use function::FunctionInfo;
+use param::ParamInfo;
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{parse_macro_input, DeriveInput};
-use table::TableInfo;
+use syn::{parse_macro_input, DeriveInput, ForeignItemFn, ItemConst};
+use table::{FieldPart, TableInfo};
 
+mod access;
+mod analyzer;
+mod doc;
+mod duration;
+mod event;
 mod field;
+mod function;
+mod index;
+mod model;
+mod param;
 mod permission;
+mod record_id;
+mod rename;
+mod scope;
+mod serde_attr;
+mod statement;
 mod table;
+mod table_options;
 mod type_conv;
+mod view;
 
 #[proc_macro_derive(
     SurQLDefinition,
     attributes(
+        surql,
         surql_query,
+        surql_query_before,
+        surql_query_after,
+        surql_access,
+        surql_analyzer,
+        surql_event,
+        surql_scope,
+        surql_search,
+        surql_vector,
         surql_table,
+        surql_table_options,
         surql_table_permissions,
         surql_field,
+        surql_index,
         surql_field_permissions
     )
 )]
@@ -24,33 +53,93 @@ pub fn surreal_db_derive(input: TokenStream) -> TokenStream {
     match TableInfo::from_derive_input(&input) {
         Ok(table_info) => {
             let struct_name = &input.ident;
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
             let method_name = format_ident!("schema_query");
-            let result = table_info.generate_define_query();
+            let statements = table_info.generate_define_statements();
+            let result = statements.join(" ");
             let query = result.trim();
+            let scoped_query = table_info.generate_scoped_query(query);
 
             #[cfg(feature = "compile_query_validation")]
-            if let Err(e) = surrealdb_core::sql::parse(&query)
-                .map_err(|err| {
-                    syn::Error::new_spanned(&input, format!("{}\nQuery: {}", err, query))
-                })
-                .map_err(|err| TokenStream::from(err.into_compile_error()))
+            for query in std::iter::once(table_info.generate_validated_statements().join(" "))
+                .chain(table_info.generate_scope_query())
             {
-                return e;
-            };
+                if let Err(e) = surrealdb_core::syn::parse(&query)
+                    .map_err(|err| {
+                        syn::Error::new_spanned(&input, format!("{}\nQuery: {}", err, query))
+                    })
+                    .map_err(|err| TokenStream::from(err.into_compile_error()))
+                {
+                    return e;
+                };
+            }
 
-            let expanded = quote! {
-                impl SurQLSchemaProducer for #struct_name {
-                    fn #method_name() -> &'static str {
-                        const SQL: &'static str = concat!(#query);
+            let (schema_method, scoped_method, statements_method) =
+                if table_info.has_runtime_schema() {
+                    runtime_schema_methods(&table_info)
+                } else {
+                    let validation = runtime_validation(&table_info, false);
+                    let scoped_method = scoped_query.map(|scoped_query| {
+                        let validation = runtime_validation(&table_info, true);
+                        quote! {
+                            fn scoped_schema_query() -> &'static str {
+                                const SQL: &'static str = concat!(#scoped_query);
+
+                                #validation
+
+                                SQL
+                            }
+                        }
+                    });
+                    let schema_method = quote! {
+                        fn #method_name() -> &'static str {
+                            const SQL: &'static str = concat!(#query);
+
+                            #validation
+
+                            SQL
+                        }
+                    };
+                    let statements_method = quote! {
+                        fn schema_statements() -> &'static [&'static str] {
+                            const STATEMENTS: &'static [&'static str] = &[#(#statements),*];
 
-                        #[cfg(feature = "runtime_query_validation")]
-                        if let Err(e) = surrealdb_core::sql::parse(&SQL) {
-                            panic!("{}", e.to_string());
+                            STATEMENTS
                         }
+                    };
+                    (schema_method, scoped_method, statements_method)
+                };
 
-                       SQL
+            let krate = model::crate_path();
+            let definition_method = schema_definition_method(&table_info);
+
+            let table_name = table_info.table_name();
+
+            let record_id_method = table_info.record_id_kind().map(|kind| {
+                let kind = model::record_id_kind_tokens(&krate, kind);
+                quote! {
+                    fn record_id_kind() -> Option<#krate::RecordIdKind> {
+                        Some(#kind)
                     }
                 }
+            });
+
+            let expanded = quote! {
+                impl #impl_generics SurQLSchemaProducer for #struct_name #ty_generics #where_clause {
+                    #schema_method
+
+                    #scoped_method
+
+                    #statements_method
+
+                    #definition_method
+
+                    #record_id_method
+                }
+
+                impl #impl_generics #krate::SurQLTable for #struct_name #ty_generics #where_clause {
+                    const TABLE_NAME: &'static str = #table_name;
+                }
             };
 
             TokenStream::from(expanded)
@@ -58,3 +147,232 @@ pub fn surreal_db_derive(input: TokenStream) -> TokenStream {
         Err(err) => TokenStream::from(err.into_compile_error()),
     }
 }
+
+/// Generates `schema_definition`. The fields, indexes, analyzers and events
+/// of flattened fields are taken from the definition of their type when it
+/// is called.
+fn schema_definition_method(table_info: &TableInfo) -> proc_macro2::TokenStream {
+    let krate = model::crate_path();
+    let definition = table_info.to_definition();
+    let table = if table_info.has_flattened_fields() {
+        let field_statements = table_info.field_parts().into_iter().map(|part| match part {
+            FieldPart::Field(field) => {
+                let field = model::field_tokens(&krate, &field.to_definition());
+                quote! { fields.push(#field); }
+            }
+            FieldPart::Flattened(ty) => quote! {
+                if let Some(definition) = <#ty as SurQLSchemaProducer>::schema_definition() {
+                    fields.extend(definition.fields.iter().cloned());
+                    flattened.push(definition);
+                }
+            },
+        });
+        let field_statements = field_statements.collect::<Vec<_>>();
+        let table_name = &definition.name;
+        let table = model::table_tokens(&krate, &definition, quote! { fields });
+        // Flattened types may define a column the struct already has, and
+        // bring their indexes, analyzers and events along
+        quote! {{
+            let mut fields = Vec::new();
+            let mut flattened = Vec::new();
+            #(#field_statements)*
+
+            let mut names = ::std::collections::HashSet::new();
+            for field in &fields {
+                if !names.insert(field.name.as_str()) {
+                    panic!(
+                        "Field `{}` is defined more than once on table `{}` through flattened fields",
+                        field.name, #table_name
+                    );
+                }
+            }
+
+            let mut definition = #table;
+            for flattened in flattened {
+                definition.include_flattened(flattened);
+            }
+            definition
+        }}
+    } else {
+        let fields = definition
+            .fields
+            .iter()
+            .map(|field| model::field_tokens(&krate, field));
+        model::table_tokens(&krate, &definition, quote! { vec![#(#fields),*] })
+    };
+
+    // The tables relations point to are filled in from their type
+    let (in_type, out_type) = table_info.relation_types();
+    let relation_tables = [("in_tables", in_type), ("out_tables", out_type)]
+        .into_iter()
+        .filter_map(|(name, ty)| {
+            let ty = ty?;
+            let name = format_ident!("{}", name);
+            Some(quote! {
+                if let Some(#krate::TableType::Relation { #name, .. }) = &mut definition.table_type {
+                    *#name = Some(String::from(<#ty as #krate::SurQLTable>::TABLE_NAME));
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let body = if relation_tables.is_empty() {
+        quote! { Some(#table) }
+    } else {
+        quote! {
+            let mut definition = #table;
+            #(#relation_tables)*
+            Some(definition)
+        }
+    };
+
+    quote! {
+        fn schema_definition() -> Option<#krate::TableDefinition> {
+            #body
+        }
+    }
+}
+
+/// Builds `schema_query`, `scoped_schema_query` and `schema_statements`
+/// once at runtime from `schema_definition`, as the definitions of flattened
+/// fields and the tables of relations come from the implementation of their
+/// type.
+fn runtime_schema_methods(
+    table_info: &TableInfo,
+) -> (
+    proc_macro2::TokenStream,
+    Option<proc_macro2::TokenStream>,
+    proc_macro2::TokenStream,
+) {
+    let statements_method = quote! {
+        fn schema_statements() -> &'static [&'static str] {
+            static STATEMENTS: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+            static SLICES: std::sync::OnceLock<Vec<&'static str>> = std::sync::OnceLock::new();
+
+            SLICES.get_or_init(|| {
+                STATEMENTS
+                    .get_or_init(|| {
+                        Self::schema_definition()
+                            .map(|definition| definition.render_statements())
+                            .unwrap_or_default()
+                    })
+                    .iter()
+                    .map(String::as_str)
+                    .collect()
+            })
+        }
+    };
+
+    let validation = runtime_validation(table_info, false);
+    let schema_method = quote! {
+        fn schema_query() -> &'static str {
+            static SQL: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+            SQL.get_or_init(|| {
+                #validation
+
+                Self::schema_statements().join(" ")
+            })
+        }
+    };
+
+    let scoped_method = table_info.generate_scope_query().map(|scope| {
+        quote! {
+            fn scoped_schema_query() -> &'static str {
+                static SQL: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+
+                SQL.get_or_init(|| format!("{} {}", #scope, Self::schema_query()))
+            }
+        }
+    });
+
+    (schema_method, scoped_method, statements_method)
+}
+
+/// Parses the schema when `runtime_query_validation` is enabled, leaving out
+/// the statements SurrealDB 2.x doesn't parse anymore. `scoped` prefixes it
+/// with the namespace and database statements.
+fn runtime_validation(table_info: &TableInfo, scoped: bool) -> proc_macro2::TokenStream {
+    let query = if table_info.has_runtime_schema() {
+        quote! {
+            Self::schema_definition()
+                .map(|mut definition| {
+                    definition.accesses.retain(|access| !access.legacy_scope);
+                    definition.render_statements().join(" ")
+                })
+                .unwrap_or_default()
+        }
+    } else {
+        let statements = table_info.generate_validated_statements().join(" ");
+        quote! { #statements }
+    };
+    let scope = table_info
+        .generate_scope_query()
+        .filter(|_| scoped)
+        .unwrap_or_default();
+
+    quote! {
+        #[cfg(feature = "runtime_query_validation")]
+        {
+            let query = format!("{} {}", #scope, #query);
+            if let Err(e) = surrealdb_core::syn::parse(query.trim()) {
+                panic!("Failed to parse query: {}", e);
+            }
+        }
+    }
+}
+
+/// Generates a `DEFINE PARAM` statement from a Rust `const`, exposed as a
+/// `<NAME>_SURQL` const next to it.
+#[proc_macro_attribute]
+pub fn surql_param(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut param_info = ParamInfo::default();
+    let parser = syn::meta::parser(|meta| param_info.parse_nested_meta(meta));
+    parse_macro_input!(attr with parser);
+    let item = parse_macro_input!(item as ItemConst);
+
+    match param_info.generate_define_query(&item) {
+        Ok(query) => {
+            #[cfg(feature = "compile_query_validation")]
+            if let Err(err) = surrealdb_core::syn::parse(&query) {
+                return TokenStream::from(
+                    syn::Error::new_spanned(&item, format!("{}\nQuery: {}", err, query))
+                        .into_compile_error(),
+                );
+            }
+
+            TokenStream::from(param_info.expand(&item, &query))
+        }
+        Err(err) => TokenStream::from(err.into_compile_error()),
+    }
+}
+
+/// Generates a `DEFINE FUNCTION` statement from a bodiless Rust signature,
+/// exposed as a `<NAME>_SURQL` const, and replaces the signature with a
+/// helper building the `fn::name(...)` call expression.
+#[proc_macro_attribute]
+pub fn surql_function(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut function_info = FunctionInfo::default();
+    let parser = syn::meta::parser(|meta| function_info.parse_nested_meta(meta));
+    parse_macro_input!(attr with parser);
+    let item = parse_macro_input!(item as ForeignItemFn);
+
+    let expanded = function_info
+        .generate_define_query(&item)
+        .and_then(|query| {
+            #[cfg(feature = "compile_query_validation")]
+            if let Err(err) = surrealdb_core::syn::parse(&query) {
+                return Err(syn::Error::new_spanned(
+                    &item,
+                    format!("{}\nQuery: {}", err, query),
+                ));
+            }
+
+            function_info.expand(&item, &query)
+        });
+
+    match expanded {
+        Ok(expanded) => TokenStream::from(expanded),
+        Err(err) => TokenStream::from(err.into_compile_error()),
+    }
+}
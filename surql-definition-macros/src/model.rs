@@ -0,0 +1,330 @@
+use proc_macro2::{Span, TokenStream};
+use proc_macro_crate::{crate_name, FoundCrate};
+use quote::quote;
+use surql_definition_core::{
+    AccessDefinition, AnalyzerDefinition, Bm25, Changefeed, DefinitionMode, EventDefinition,
+    FieldDefinition, IndexDefinition, IndexKind, PermissionDefinition, PermissionOperation,
+    RecordIdKind, TableDefinition, TableType, VectorAlgorithm,
+};
+use syn::Ident;
+
+/// The path to the crate exporting the schema model, which is either the
+/// core crate or the `surql-definition` facade.
+pub(crate) fn crate_path() -> TokenStream {
+    for name in ["surql-definition-core", "surql-definition"] {
+        match crate_name(name) {
+            Ok(FoundCrate::Itself) => return quote! { crate },
+            Ok(FoundCrate::Name(name)) => {
+                let ident = Ident::new(&name, Span::call_site());
+                return quote! { ::#ident };
+            }
+            Err(_) => {}
+        }
+    }
+    quote! { ::surql_definition_core }
+}
+
+/// Builds `definition` at runtime, with `fields` as the expression of its
+/// fields.
+pub(crate) fn table_tokens(
+    krate: &TokenStream,
+    definition: &TableDefinition,
+    fields: TokenStream,
+) -> TokenStream {
+    let TableDefinition {
+        name,
+        mode,
+        drop,
+        table_type,
+        view,
+        changefeed,
+        permissions,
+        comment,
+        fields: _,
+        analyzers,
+        indexes,
+        events,
+        accesses,
+        custom_query,
+        queries_before,
+        queries_after,
+    } = definition;
+
+    let mode = mode_tokens(krate, *mode);
+    let table_type = option_tokens(table_type.as_ref(), |ty| table_type_tokens(krate, ty));
+    let view = option_tokens(view.as_ref(), string_tokens);
+    let changefeed = option_tokens(changefeed.as_ref(), |changefeed| {
+        let Changefeed {
+            duration,
+            include_original,
+        } = changefeed;
+        quote! {
+            #krate::Changefeed {
+                duration: String::from(#duration),
+                include_original: #include_original,
+            }
+        }
+    });
+    let permissions = option_tokens(permissions.as_ref(), |perms| {
+        permission_tokens(krate, perms)
+    });
+    let comment = option_tokens(comment.as_ref(), string_tokens);
+    let analyzers = analyzers.iter().map(|analyzer| {
+        let AnalyzerDefinition {
+            name,
+            tokenizers,
+            filters,
+        } = analyzer;
+        let tokenizers = vec_tokens(tokenizers);
+        let filters = vec_tokens(filters);
+        quote! {
+            #krate::AnalyzerDefinition {
+                name: String::from(#name),
+                tokenizers: #tokenizers,
+                filters: #filters,
+            }
+        }
+    });
+    let indexes = indexes.iter().map(|index| index_tokens(krate, index));
+    let events = events.iter().map(|event| {
+        let EventDefinition { name, when, then } = event;
+        let when = option_tokens(when.as_ref(), string_tokens);
+        quote! {
+            #krate::EventDefinition {
+                name: String::from(#name),
+                when: #when,
+                then: String::from(#then),
+            }
+        }
+    });
+    let accesses = accesses.iter().map(|access| access_tokens(krate, access));
+    let custom_query = option_tokens(custom_query.as_ref(), |query| vec_tokens(query));
+    let queries_before = vec_tokens(queries_before);
+    let queries_after = vec_tokens(queries_after);
+
+    quote! {
+        #krate::TableDefinition {
+            name: String::from(#name),
+            mode: #mode,
+            drop: #drop,
+            table_type: #table_type,
+            view: #view,
+            changefeed: #changefeed,
+            permissions: #permissions,
+            comment: #comment,
+            fields: #fields,
+            analyzers: vec![#(#analyzers),*],
+            indexes: vec![#(#indexes),*],
+            events: vec![#(#events),*],
+            accesses: vec![#(#accesses),*],
+            custom_query: #custom_query,
+            queries_before: #queries_before,
+            queries_after: #queries_after,
+        }
+    }
+}
+
+pub(crate) fn field_tokens(krate: &TokenStream, field: &FieldDefinition) -> TokenStream {
+    let FieldDefinition {
+        name,
+        flexible,
+        field_type,
+        default,
+        value,
+        readonly,
+        assertion,
+        permissions,
+        comment,
+    } = field;
+
+    let field_type = option_tokens(field_type.as_ref(), string_tokens);
+    let default = option_tokens(default.as_ref(), string_tokens);
+    let value = option_tokens(value.as_ref(), string_tokens);
+    let assertion = option_tokens(assertion.as_ref(), string_tokens);
+    let permissions = option_tokens(permissions.as_ref(), |perms| {
+        permission_tokens(krate, perms)
+    });
+    let comment = option_tokens(comment.as_ref(), string_tokens);
+
+    quote! {
+        #krate::FieldDefinition {
+            name: String::from(#name),
+            flexible: #flexible,
+            field_type: #field_type,
+            default: #default,
+            value: #value,
+            readonly: #readonly,
+            assertion: #assertion,
+            permissions: #permissions,
+            comment: #comment,
+        }
+    }
+}
+
+fn access_tokens(krate: &TokenStream, access: &AccessDefinition) -> TokenStream {
+    let AccessDefinition {
+        name,
+        identifier,
+        password,
+        session,
+        token,
+        legacy_scope,
+    } = access;
+    let session = option_tokens(session.as_ref(), string_tokens);
+    let token = option_tokens(token.as_ref(), string_tokens);
+
+    quote! {
+        #krate::AccessDefinition {
+            name: String::from(#name),
+            identifier: String::from(#identifier),
+            password: String::from(#password),
+            session: #session,
+            token: #token,
+            legacy_scope: #legacy_scope,
+        }
+    }
+}
+
+pub(crate) fn record_id_kind_tokens(krate: &TokenStream, kind: RecordIdKind) -> TokenStream {
+    match kind {
+        RecordIdKind::String => quote! { #krate::RecordIdKind::String },
+        RecordIdKind::Integer => quote! { #krate::RecordIdKind::Integer },
+        RecordIdKind::Uuid => quote! { #krate::RecordIdKind::Uuid },
+        RecordIdKind::Array => quote! { #krate::RecordIdKind::Array },
+        RecordIdKind::Object => quote! { #krate::RecordIdKind::Object },
+        RecordIdKind::Any => quote! { #krate::RecordIdKind::Any },
+    }
+}
+
+fn mode_tokens(krate: &TokenStream, mode: DefinitionMode) -> TokenStream {
+    match mode {
+        DefinitionMode::Default => quote! { #krate::DefinitionMode::Default },
+        DefinitionMode::IfNotExists => quote! { #krate::DefinitionMode::IfNotExists },
+        DefinitionMode::Overwrite => quote! { #krate::DefinitionMode::Overwrite },
+    }
+}
+
+fn table_type_tokens(krate: &TokenStream, table_type: &TableType) -> TokenStream {
+    match table_type {
+        TableType::Any => quote! { #krate::TableType::Any },
+        TableType::Normal => quote! { #krate::TableType::Normal },
+        TableType::Relation {
+            in_tables,
+            out_tables,
+            enforced,
+        } => {
+            let in_tables = option_tokens(in_tables.as_ref(), string_tokens);
+            let out_tables = option_tokens(out_tables.as_ref(), string_tokens);
+            quote! {
+                #krate::TableType::Relation {
+                    in_tables: #in_tables,
+                    out_tables: #out_tables,
+                    enforced: #enforced,
+                }
+            }
+        }
+    }
+}
+
+fn permission_tokens(krate: &TokenStream, permissions: &PermissionDefinition) -> TokenStream {
+    match permissions {
+        PermissionDefinition::None => quote! { #krate::PermissionDefinition::None },
+        PermissionDefinition::Full => quote! { #krate::PermissionDefinition::Full },
+        PermissionDefinition::Rules(rules) => {
+            let rules = rules.iter().map(|rule| {
+                let operations = rule.operations.iter().map(|operation| match operation {
+                    PermissionOperation::Select => quote! { #krate::PermissionOperation::Select },
+                    PermissionOperation::Create => quote! { #krate::PermissionOperation::Create },
+                    PermissionOperation::Update => quote! { #krate::PermissionOperation::Update },
+                    PermissionOperation::Delete => quote! { #krate::PermissionOperation::Delete },
+                });
+                let condition = &rule.condition;
+                quote! {
+                    #krate::PermissionRule {
+                        operations: vec![#(#operations),*],
+                        condition: String::from(#condition),
+                    }
+                }
+            });
+            quote! { #krate::PermissionDefinition::Rules(vec![#(#rules),*]) }
+        }
+    }
+}
+
+fn index_tokens(krate: &TokenStream, index: &IndexDefinition) -> TokenStream {
+    let name = &index.name;
+    let fields = vec_tokens(&index.fields);
+    let kind = match &index.kind {
+        IndexKind::Standard => quote! { #krate::IndexKind::Standard },
+        IndexKind::Unique => quote! { #krate::IndexKind::Unique },
+        IndexKind::Search(search) => {
+            let analyzer = &search.analyzer;
+            let bm25 = option_tokens(search.bm25.as_ref(), |bm25| match bm25 {
+                Bm25::Default => quote! { #krate::Bm25::Default },
+                Bm25::Parameters { k1, b } => quote! {
+                    #krate::Bm25::Parameters {
+                        k1: String::from(#k1),
+                        b: String::from(#b),
+                    }
+                },
+            });
+            let highlights = search.highlights;
+            quote! {
+                #krate::IndexKind::Search(#krate::SearchIndex {
+                    analyzer: String::from(#analyzer),
+                    bm25: #bm25,
+                    highlights: #highlights,
+                })
+            }
+        }
+        IndexKind::Vector(vector) => {
+            let algorithm = match vector.algorithm {
+                VectorAlgorithm::Mtree => quote! { #krate::VectorAlgorithm::Mtree },
+                VectorAlgorithm::Hnsw => quote! { #krate::VectorAlgorithm::Hnsw },
+            };
+            let dimension = option_tokens(vector.dimension.as_ref(), |value| quote! { #value });
+            let vector_type = option_tokens(vector.vector_type.as_ref(), string_tokens);
+            let distance = option_tokens(vector.distance.as_ref(), string_tokens);
+            let capacity = option_tokens(vector.capacity.as_ref(), |value| quote! { #value });
+            let efc = option_tokens(vector.efc.as_ref(), |value| quote! { #value });
+            let m = option_tokens(vector.m.as_ref(), |value| quote! { #value });
+            quote! {
+                #krate::IndexKind::Vector(#krate::VectorIndex {
+                    algorithm: #algorithm,
+                    dimension: #dimension,
+                    vector_type: #vector_type,
+                    distance: #distance,
+                    capacity: #capacity,
+                    efc: #efc,
+                    m: #m,
+                })
+            }
+        }
+    };
+
+    quote! {
+        #krate::IndexDefinition {
+            name: String::from(#name),
+            fields: #fields,
+            kind: #kind,
+        }
+    }
+}
+
+fn string_tokens(value: &String) -> TokenStream {
+    quote! { String::from(#value) }
+}
+
+fn vec_tokens(values: &[String]) -> TokenStream {
+    quote! { vec![#(String::from(#values)),*] }
+}
+
+fn option_tokens<T>(value: Option<&T>, tokens: impl FnOnce(&T) -> TokenStream) -> TokenStream {
+    match value {
+        Some(value) => {
+            let value = tokens(value);
+            quote! { Some(#value) }
+        }
+        None => quote! { None },
+    }
+}
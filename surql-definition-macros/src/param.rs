@@ -0,0 +1,63 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{meta::ParseNestedMeta, ItemConst, LitStr, Result};
+
+use crate::{
+    table_options::{parse_definition_mode, DefinitionMode},
+    type_conv::format_expr_as_literal,
+};
+
+/// A `DEFINE PARAM` generated from a Rust `const` with `#[surql_param]`.
+#[derive(Default)]
+pub(crate) struct ParamInfo {
+    name: Option<String>,
+    mode: DefinitionMode,
+}
+
+impl ParamInfo {
+    /// Parses the `name = "..."`, `IF_NOT_EXISTS` and `OVERWRITE` arguments
+    /// of `#[surql_param(...)]`.
+    pub(crate) fn parse_nested_meta(&mut self, meta: ParseNestedMeta) -> Result<()> {
+        if meta.path.is_ident("name") {
+            let lit: LitStr = meta.value()?.parse()?;
+            self.name = Some(lit.value().trim_start_matches('$').to_string());
+            Ok(())
+        } else if parse_definition_mode(&mut self.mode, &meta)? {
+            Ok(())
+        } else {
+            Err(meta.error("Unrecognized param attribute"))
+        }
+    }
+
+    /// The `DEFINE PARAM` statement. The parameter is named after the
+    /// lowercased const unless `name` is set.
+    pub(crate) fn generate_define_query(&self, item: &ItemConst) -> Result<String> {
+        let name = self
+            .name
+            .clone()
+            .unwrap_or_else(|| item.ident.to_string().to_lowercase());
+        let value = format_expr_as_literal(&item.expr)?;
+
+        Ok(format!(
+            "{} ${} VALUE {};",
+            self.mode.define("PARAM"),
+            name,
+            value
+        ))
+    }
+
+    /// Keeps the const untouched and adds a `<NAME>_SURQL` const holding the
+    /// statement next to it.
+    pub(crate) fn expand(&self, item: &ItemConst, query: &str) -> TokenStream {
+        let vis = &item.vis;
+        let surql_ident = format_ident!("{}_SURQL", item.ident);
+        let doc = format!("SurrealQL definition of [`{}`].", item.ident);
+
+        quote! {
+            #item
+
+            #[doc = #doc]
+            #vis const #surql_ident: &'static str = #query;
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt;
 
-use syn::{meta::ParseNestedMeta, Lit};
+use surql_definition_core::{PermissionDefinition, PermissionOperation, PermissionRule};
+use syn::{meta::ParseNestedMeta, punctuated::Punctuated, Ident, Lit, LitStr, Token};
 
 use crate::type_conv::format_lit_as_expr;
 
@@ -15,24 +16,36 @@ impl From<String> for PermissionData {
     }
 }
 
-impl Into<String> for PermissionData {
-    fn into(self) -> String {
-        self.value
+impl From<PermissionData> for String {
+    fn from(value: PermissionData) -> Self {
+        value.value
     }
 }
 
-impl ToString for PermissionData {
-    fn to_string(&self) -> String {
-        self.value.clone()
+impl fmt::Display for PermissionData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+fn parse_operation(name: &str) -> Option<PermissionOperation> {
+    match name.to_ascii_lowercase().as_str() {
+        "select" => Some(PermissionOperation::Select),
+        "create" => Some(PermissionOperation::Create),
+        "update" => Some(PermissionOperation::Update),
+        "delete" => Some(PermissionOperation::Delete),
+        _ => None,
     }
 }
 
 #[derive(Clone)]
 pub(crate) enum PermissionInfo {
-    Select(PermissionData),
-    Create(PermissionData),
-    Update(PermissionData),
-    Delete(PermissionData),
+    None,
+    Full,
+    For {
+        operations: Vec<PermissionOperation>,
+        value: PermissionData,
+    },
 }
 
 impl TryFrom<ParseNestedMeta<'_>> for PermissionInfo {
@@ -45,34 +58,80 @@ impl TryFrom<ParseNestedMeta<'_>> for PermissionInfo {
             .map(|ident| ident.to_string())
             .unwrap_or_default();
 
+        let operations = match attribute_name.as_str() {
+            "NONE" => return Ok(Self::None),
+            "FULL" => return Ok(Self::Full),
+            "SELECT" => vec![PermissionOperation::Select],
+            "CREATE" => vec![PermissionOperation::Create],
+            "UPDATE" => vec![PermissionOperation::Update],
+            "DELETE" => vec![PermissionOperation::Delete],
+            "FOR" => {
+                let content;
+                syn::parenthesized!(content in meta.input);
+                let idents = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                let operations = idents
+                    .iter()
+                    .map(|ident| {
+                        parse_operation(&ident.to_string()).ok_or_else(|| {
+                            syn::Error::new_spanned(ident, "Unrecognized permission operation")
+                        })
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+                if operations.is_empty() {
+                    return Err(meta.error("FOR requires at least one operation"));
+                }
+                operations
+            }
+            _ => return Err(meta.error("Unrecognized permission type")),
+        };
+
         let lit: Lit = meta.value()?.parse()?;
         let perm_value = format_lit_as_expr(lit);
 
-        match attribute_name.as_str() {
-            "SELECT" => Ok(Self::Select(PermissionData::from(perm_value))),
-            "CREATE" => Ok(Self::Create(PermissionData::from(perm_value))),
-            "UPDATE" => Ok(Self::Update(PermissionData::from(perm_value))),
-            "DELETE" => Ok(Self::Delete(PermissionData::from(perm_value))),
-            _ => Err(meta.error("Unrecognized permission type")),
-        }
+        Ok(Self::For {
+            operations,
+            value: PermissionData::from(perm_value),
+        })
     }
 }
 
-impl fmt::Display for PermissionInfo {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let formatted_string: String = match self {
-            PermissionInfo::Select(inner) => format!("FOR select {}", inner.to_string()),
-            PermissionInfo::Create(inner) => format!("FOR create {}", inner.to_string()),
-            PermissionInfo::Update(inner) => format!("FOR update {}", inner.to_string()),
-            PermissionInfo::Delete(inner) => format!("FOR delete {}", inner.to_string()),
-        };
-        write!(f, "{}", formatted_string)
-    }
+/// How repeated clauses for the same operation are combined.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MergeStrategy {
+    And,
+    Or,
 }
 
-impl Into<String> for PermissionInfo {
-    fn into(self) -> String {
-        self.to_string()
+/// The permissions collected from every `surql_*_permissions` attribute of a
+/// table or field.
+#[derive(Clone, Default)]
+pub(crate) struct PermissionSet {
+    permissions: Vec<PermissionInfo>,
+    merge: Option<MergeStrategy>,
+}
+
+impl PermissionSet {
+    pub(crate) fn parse_nested_meta(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("MERGE") {
+            let lit: LitStr = meta.value()?.parse()?;
+            let strategy = match lit.value().to_ascii_uppercase().as_str() {
+                "AND" => MergeStrategy::And,
+                "OR" => MergeStrategy::Or,
+                _ => return Err(syn::Error::new_spanned(lit, "Expected \"AND\" or \"OR\"")),
+            };
+            if self.merge.is_some_and(|merge| merge != strategy) {
+                return Err(meta.error("Conflicting MERGE strategies"));
+            }
+            self.merge = Some(strategy);
+            return Ok(());
+        }
+
+        self.permissions.push(parse_permissions_attributes(meta)?);
+        Ok(())
+    }
+
+    pub(crate) fn build(self) -> Result<Option<PermissionDefinition>, String> {
+        build_permissions(self.permissions, self.merge)
     }
 }
 
@@ -80,15 +139,234 @@ pub(crate) fn parse_permissions_attributes(meta: ParseNestedMeta) -> syn::Result
     PermissionInfo::try_from(meta)
 }
 
-pub(crate) fn format_permissions(permissions: Vec<PermissionInfo>) -> Option<String> {
-    let permissions_str: Vec<String> = permissions
-        .into_iter()
-        .map(|x| x.into())
-        .collect::<Vec<_>>();
+pub(crate) fn build_permissions(
+    permissions: Vec<PermissionInfo>,
+    merge: Option<MergeStrategy>,
+) -> Result<Option<PermissionDefinition>, String> {
+    let is_global =
+        |perm: &PermissionInfo| matches!(perm, PermissionInfo::None | PermissionInfo::Full);
+    if permissions.len() > 1 && permissions.iter().any(is_global) {
+        return Err(
+            "NONE and FULL permissions can't be combined with other permissions".to_string(),
+        );
+    }
+
+    let mut rules = vec![];
+    for perm in merge_permissions(permissions, merge)? {
+        match perm {
+            PermissionInfo::None => return Ok(Some(PermissionDefinition::None)),
+            PermissionInfo::Full => return Ok(Some(PermissionDefinition::Full)),
+            PermissionInfo::For { operations, value } => rules.push(PermissionRule {
+                operations,
+                condition: value.into(),
+            }),
+        }
+    }
 
-    if permissions_str.is_empty() {
-        None
+    if rules.is_empty() {
+        Ok(None)
     } else {
-        Some(permissions_str.join(" "))
+        Ok(Some(PermissionDefinition::Rules(rules)))
+    }
+}
+
+/// Combines clauses that target the same operation so each operation is only
+/// emitted once. Clauses are left untouched when no operation is repeated.
+fn merge_permissions(
+    permissions: Vec<PermissionInfo>,
+    merge: Option<MergeStrategy>,
+) -> Result<Vec<PermissionInfo>, String> {
+    let mut merged: Vec<(PermissionOperation, Vec<String>)> = vec![];
+    for perm in &permissions {
+        if let PermissionInfo::For { operations, value } = perm {
+            for operation in operations {
+                let value = value.to_string();
+                match merged.iter_mut().find(|(op, _)| op == operation) {
+                    Some((_, values)) if !values.contains(&value) => values.push(value),
+                    Some(_) => {}
+                    None => merged.push((*operation, vec![value])),
+                }
+            }
+        }
+    }
+
+    if merged.iter().all(|(_, values)| values.len() == 1)
+        && merged.len()
+            == permissions
+                .iter()
+                .map(|perm| match perm {
+                    PermissionInfo::For { operations, .. } => operations.len(),
+                    _ => 0,
+                })
+                .sum::<usize>()
+    {
+        return Ok(permissions);
+    }
+
+    let mut clauses: Vec<(Vec<PermissionOperation>, String)> = vec![];
+    for (operation, values) in merged {
+        let value = if values.len() == 1 {
+            values.into_iter().next().unwrap_or_default()
+        } else {
+            let strategy = merge.ok_or_else(|| {
+                format!(
+                    "Conflicting permissions for {}; set MERGE = \"AND\" or MERGE = \"OR\" to combine them",
+                    operation
+                )
+            })?;
+            merge_values(operation, values, strategy)?
+        };
+
+        match clauses.iter_mut().find(|(_, v)| *v == value) {
+            Some((operations, _)) => operations.push(operation),
+            None => clauses.push((vec![operation], value)),
+        }
+    }
+
+    Ok(clauses
+        .into_iter()
+        .map(|(operations, value)| PermissionInfo::For {
+            operations,
+            value: PermissionData::from(value),
+        })
+        .collect())
+}
+
+fn merge_values(
+    operation: PermissionOperation,
+    values: Vec<String>,
+    strategy: MergeStrategy,
+) -> Result<String, String> {
+    let mut conditions = vec![];
+    for value in &values {
+        let trimmed = value.trim();
+        match trimmed.to_ascii_uppercase().as_str() {
+            "FULL" if strategy == MergeStrategy::Or => return Ok("FULL".to_string()),
+            "NONE" if strategy == MergeStrategy::And => return Ok("NONE".to_string()),
+            "FULL" | "NONE" => {}
+            _ => {
+                let condition = trimmed
+                    .get(..6)
+                    .filter(|prefix| prefix.eq_ignore_ascii_case("WHERE "))
+                    .map(|_| trimmed[6..].trim())
+                    .ok_or_else(|| {
+                        format!(
+                            "Ambiguous permissions for {}: `{}` is not a WHERE clause and can't be merged",
+                            operation, trimmed
+                        )
+                    })?;
+                conditions.push(format!("({})", condition));
+            }
+        }
+    }
+
+    if conditions.is_empty() {
+        // Only the neutral element of the strategy was declared
+        return Ok(match strategy {
+            MergeStrategy::And => "FULL".to_string(),
+            MergeStrategy::Or => "NONE".to_string(),
+        });
+    }
+
+    let operator = match strategy {
+        MergeStrategy::And => " AND ",
+        MergeStrategy::Or => " OR ",
+    };
+    Ok(format!("WHERE {}", conditions.join(operator)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(operation: PermissionOperation, value: &str) -> PermissionInfo {
+        PermissionInfo::For {
+            operations: vec![operation],
+            value: PermissionData::from(value.to_string()),
+        }
+    }
+
+    #[test]
+    fn merges_where_clauses() {
+        let values = vec!["WHERE a = 1".to_string(), "where b = 2".to_string()];
+        assert_eq!(
+            merge_values(
+                PermissionOperation::Select,
+                values.clone(),
+                MergeStrategy::And
+            ),
+            Ok("WHERE (a = 1) AND (b = 2)".to_string())
+        );
+        assert_eq!(
+            merge_values(PermissionOperation::Select, values, MergeStrategy::Or),
+            Ok("WHERE (a = 1) OR (b = 2)".to_string())
+        );
+    }
+
+    #[test]
+    fn merges_absorbing_and_neutral_values() {
+        let values = vec!["FULL".to_string(), "WHERE a = 1".to_string()];
+        assert_eq!(
+            merge_values(
+                PermissionOperation::Update,
+                values.clone(),
+                MergeStrategy::Or
+            ),
+            Ok("FULL".to_string())
+        );
+        assert_eq!(
+            merge_values(PermissionOperation::Update, values, MergeStrategy::And),
+            Ok("WHERE (a = 1)".to_string())
+        );
+        assert_eq!(
+            merge_values(
+                PermissionOperation::Update,
+                vec!["FULL".to_string(), "FULL".to_string()],
+                MergeStrategy::And
+            ),
+            Ok("FULL".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_values_that_are_not_where_clauses() {
+        assert_eq!(
+            merge_values(
+                PermissionOperation::Delete,
+                vec!["WHERE a = 1".to_string(), "$auth.admin".to_string()],
+                MergeStrategy::And
+            ),
+            Err(
+                "Ambiguous permissions for delete: `$auth.admin` is not a WHERE clause and can't be merged"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_conflicts_without_merge_strategy() {
+        let permissions = vec![
+            rule(PermissionOperation::Select, "WHERE a = 1"),
+            rule(PermissionOperation::Select, "WHERE b = 2"),
+        ];
+        assert_eq!(
+            build_permissions(permissions, None),
+            Err(
+                "Conflicting permissions for select; set MERGE = \"AND\" or MERGE = \"OR\" to combine them"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_global_permissions_with_others() {
+        let permissions = vec![
+            PermissionInfo::Full,
+            rule(PermissionOperation::Select, "WHERE a = 1"),
+        ];
+        assert_eq!(
+            build_permissions(permissions, Some(MergeStrategy::And)),
+            Err("NONE and FULL permissions can't be combined with other permissions".to_string())
+        );
     }
 }
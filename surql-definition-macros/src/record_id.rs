@@ -0,0 +1,113 @@
+use surql_definition_core::RecordIdKind;
+use syn::{Error, Field, GenericArgument, LitStr, PathArguments, PathSegment, Result, Type};
+
+/// The record id of a table, inferred from the type of its `id` field.
+#[derive(Clone)]
+pub(crate) struct RecordIdInfo {
+    pub kind: RecordIdKind,
+}
+
+const RECORD_ID_KINDS: [&str; 6] = ["string", "int", "uuid", "array", "object", "any"];
+
+impl RecordIdInfo {
+    /// The `id` field isn't defined as a regular field, so only `TYPE` is
+    /// accepted to override the inferred kind.
+    pub(crate) fn from_field(f: &Field) -> Result<Self> {
+        let mut kind = None;
+
+        for attr in &f.attrs {
+            if attr.path().is_ident("surql_field") {
+                attr.parse_nested_meta(|meta| {
+                    if !meta.path.is_ident("TYPE") {
+                        return Err(meta.error("The id field only accepts TYPE"));
+                    }
+                    let lit: LitStr = meta.value()?.parse()?;
+                    let value = match lit.value().as_str() {
+                        "record" => Some(RecordIdKind::Any),
+                        value => RecordIdKind::from_name(value),
+                    };
+                    let value = value.ok_or_else(|| {
+                        Error::new_spanned(
+                            &lit,
+                            format!(
+                                "Unsupported record id type {}, expected one of record, {}",
+                                lit.value(),
+                                RECORD_ID_KINDS.join(", ")
+                            ),
+                        )
+                    })?;
+                    kind = Some(value);
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("surql_field_permissions")
+                || attr.path().is_ident("surql_search")
+                || attr.path().is_ident("surql_vector")
+            {
+                return Err(Error::new_spanned(
+                    attr,
+                    "The id field can't have permissions or indexes",
+                ));
+            }
+        }
+
+        let kind = match kind {
+            Some(kind) => kind,
+            None => record_id_kind(&f.ty)?,
+        };
+        Ok(RecordIdInfo { kind })
+    }
+}
+
+/// Maps the Rust type of an `id` field to the kind of record id it holds.
+fn record_id_kind(ty: &Type) -> Result<RecordIdKind> {
+    match ty {
+        Type::Reference(type_reference) => record_id_kind(&type_reference.elem),
+        Type::Array(_) | Type::Slice(_) | Type::Tuple(_) => Ok(RecordIdKind::Array),
+        Type::Path(type_path) => {
+            let segment = type_path
+                .path
+                .segments
+                .last()
+                .ok_or_else(|| Error::new_spanned(type_path, "Path segment is missing"))?;
+            match segment.ident.to_string().as_str() {
+                "String" | "str" | "char" => Ok(RecordIdKind::String),
+                "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+                | "u128" | "usize" => Ok(RecordIdKind::Integer),
+                "Uuid" => Ok(RecordIdKind::Uuid),
+                "Vec" => Ok(RecordIdKind::Array),
+                "HashMap" | "BTreeMap" | "IndexMap" | "Map" => Ok(RecordIdKind::Object),
+                "Thing" | "RecordId" | "Value" => Ok(RecordIdKind::Any),
+                "f32" | "f64" | "bool" => Err(Error::new_spanned(
+                    segment,
+                    "Record ids must be strings, integers, uuids, arrays or objects",
+                )),
+                // Wrappers serialize as the type they hold
+                "Option" | "Box" | "Rc" | "Arc" | "Cow" => record_id_kind(inner_type(segment)?),
+                _ => Err(Error::new_spanned(
+                    segment,
+                    format!(
+                        "Unable to infer the record id kind of {}. Consider setting it explicitly using #[surql_field(TYPE = \"...\")].",
+                        segment.ident
+                    ),
+                )),
+            }
+        }
+        _ => Err(Error::new_spanned(ty, "Unsupported record id type")),
+    }
+}
+
+/// The type argument of a wrapper such as `Option<T>` or `Cow<'a, T>`.
+fn inner_type(segment: &PathSegment) -> Result<&Type> {
+    if let PathArguments::AngleBracketed(args) = &segment.arguments {
+        if let Some(inner) = args.args.iter().find_map(|arg| match arg {
+            GenericArgument::Type(inner) => Some(inner),
+            _ => None,
+        }) {
+            return Ok(inner);
+        }
+    }
+    Err(Error::new_spanned(
+        segment,
+        format!("{} type requires a generic type argument", segment.ident),
+    ))
+}
@@ -0,0 +1,159 @@
+use surql_definition_core::to_snake_case;
+use syn::{meta::ParseNestedMeta, Error, LitStr, Result};
+
+/// The `rename_all` conventions supported by serde.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+const RENAME_RULES: [(&str, RenameRule); 8] = [
+    ("lowercase", RenameRule::Lower),
+    ("UPPERCASE", RenameRule::Upper),
+    ("PascalCase", RenameRule::Pascal),
+    ("camelCase", RenameRule::Camel),
+    ("snake_case", RenameRule::Snake),
+    ("SCREAMING_SNAKE_CASE", RenameRule::ScreamingSnake),
+    ("kebab-case", RenameRule::Kebab),
+    ("SCREAMING-KEBAB-CASE", RenameRule::ScreamingKebab),
+];
+
+impl RenameRule {
+    pub(crate) fn from_lit(lit: &LitStr) -> Result<Self> {
+        let value = lit.value();
+        RENAME_RULES
+            .iter()
+            .find(|(name, _)| *name == value)
+            .map(|(_, rule)| *rule)
+            .ok_or_else(|| {
+                Error::new_spanned(
+                    lit,
+                    format!(
+                        "Unknown rename rule {}, expected one of {}",
+                        value,
+                        RENAME_RULES
+                            .iter()
+                            .map(|(name, _)| *name)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                )
+            })
+    }
+
+    /// Renames a snake_case field the way serde does.
+    pub(crate) fn apply_to_field(&self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_string(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => {
+                let mut pascal = String::new();
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+                pascal
+            }
+            RenameRule::Camel => {
+                let pascal = RenameRule::Pascal.apply_to_field(field);
+                let mut chars = pascal.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_lowercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => field.to_ascii_uppercase().replace('_', "-"),
+        }
+    }
+
+    /// Renames the snake_case form of a type name. Unlike fields, words are
+    /// joined for `lowercase` and `UPPERCASE`, as serde does for variants.
+    fn apply_to_snake(&self, snake: &str) -> String {
+        match self {
+            RenameRule::Lower => snake.replace('_', ""),
+            RenameRule::Upper => snake.replace('_', "").to_ascii_uppercase(),
+            rule => rule.apply_to_field(snake),
+        }
+    }
+}
+
+/// How table names are derived from struct names, from
+/// `#[surql(table_case = "...", plural, prefix = "...", suffix = "...")]`.
+#[derive(Default)]
+pub(crate) struct TableNaming {
+    case: Option<RenameRule>,
+    plural: bool,
+    prefix: Option<String>,
+    suffix: Option<String>,
+}
+
+impl TableNaming {
+    /// Returns `false` when the key isn't a naming setting.
+    pub(crate) fn parse_nested_meta(&mut self, meta: &ParseNestedMeta) -> Result<bool> {
+        if meta.path.is_ident("table_case") {
+            let lit: LitStr = meta.value()?.parse()?;
+            self.case = Some(RenameRule::from_lit(&lit)?);
+        } else if meta.path.is_ident("plural") {
+            self.plural = true;
+        } else if meta.path.is_ident("prefix") {
+            let lit: LitStr = meta.value()?.parse()?;
+            self.prefix = Some(lit.value());
+        } else if meta.path.is_ident("suffix") {
+            let lit: LitStr = meta.value()?.parse()?;
+            self.suffix = Some(lit.value());
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// The table name of a struct, snake_case unless another case is set.
+    pub(crate) fn table_name(&self, struct_name: &str) -> String {
+        let mut snake = to_snake_case(struct_name);
+        if self.plural {
+            snake = pluralize(&snake);
+        }
+        let name = self
+            .case
+            .unwrap_or(RenameRule::Snake)
+            .apply_to_snake(&snake);
+        format!(
+            "{}{}{}",
+            self.prefix.as_deref().unwrap_or_default(),
+            name,
+            self.suffix.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+/// English plural of the last word of a snake_case name.
+fn pluralize(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix('y') {
+        if !stem.ends_with(['a', 'e', 'i', 'o', 'u']) {
+            return format!("{}ies", stem);
+        }
+    }
+    if ["s", "x", "z", "ch", "sh"]
+        .iter()
+        .any(|ending| name.ends_with(ending))
+    {
+        format!("{}es", name)
+    } else {
+        format!("{}s", name)
+    }
+}
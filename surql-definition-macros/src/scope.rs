@@ -0,0 +1,52 @@
+use syn::{Attribute, Error, Result};
+
+use crate::table_options::DefinitionMode;
+
+/// The namespace and database a table belongs to, from `#[surql_scope(ns = "...", db = "...")]`.
+#[derive(Clone)]
+pub(crate) struct ScopeInfo {
+    namespace: String,
+    database: String,
+}
+
+impl ScopeInfo {
+    pub(crate) fn parse_attribute(attr: &Attribute) -> Result<Self> {
+        let mut namespace = None;
+        let mut database = None;
+
+        attr.parse_nested_meta(|meta| {
+            let target = if meta.path.is_ident("ns") {
+                &mut namespace
+            } else if meta.path.is_ident("db") {
+                &mut database
+            } else {
+                return Err(meta.error("Unrecognized scope attribute"));
+            };
+            let lit: syn::LitStr = meta.value()?.parse()?;
+            *target = Some(lit.value());
+            Ok(())
+        })?;
+
+        Ok(ScopeInfo {
+            namespace: namespace
+                .ok_or_else(|| Error::new_spanned(attr, "Scope requires a namespace (ns)"))?,
+            database: database
+                .ok_or_else(|| Error::new_spanned(attr, "Scope requires a database (db)"))?,
+        })
+    }
+
+    /// Statements creating and selecting the namespace and database, to be
+    /// run before the table definitions.
+    pub(crate) fn generate_define_query(&self, mode: DefinitionMode) -> String {
+        format!(
+            "{} {}; USE NS {}; {} {}; USE NS {} DB {};",
+            mode.define("NAMESPACE"),
+            self.namespace,
+            self.namespace,
+            mode.define("DATABASE"),
+            self.database,
+            self.namespace,
+            self.database
+        )
+    }
+}
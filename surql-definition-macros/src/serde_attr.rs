@@ -0,0 +1,123 @@
+use proc_macro2::Span;
+use syn::{meta::ParseNestedMeta, Attribute, Expr, LitStr, Result};
+
+use crate::rename::RenameRule;
+
+/// Where serde takes the value of a missing field from.
+#[derive(Clone)]
+pub(crate) enum SerdeDefault {
+    /// `Default::default()` of the field type.
+    Type,
+    /// A value only known at runtime, such as `default = "path"`.
+    Custom { description: String, span: Span },
+}
+
+impl SerdeDefault {
+    fn parse(meta: &ParseNestedMeta) -> Result<Self> {
+        if meta.input.peek(syn::Token![=]) {
+            let lit: LitStr = meta.value()?.parse()?;
+            Ok(SerdeDefault::Custom {
+                description: format!("`{}`", lit.value()),
+                span: lit.span(),
+            })
+        } else {
+            Ok(SerdeDefault::Type)
+        }
+    }
+}
+
+/// The `#[serde(...)]` settings of a field that affect its stored shape.
+#[derive(Default)]
+pub(crate) struct SerdeField {
+    pub rename: Option<String>,
+    /// `skip` or `skip_serializing`: the field is never stored.
+    pub skip: bool,
+    pub flatten: bool,
+    pub default: Option<SerdeDefault>,
+}
+
+impl SerdeField {
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut serde_field = SerdeField::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    if let Some(lit) = parse_serialize_name(&meta)? {
+                        serde_field.rename = Some(lit.value());
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                    serde_field.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("flatten") {
+                    serde_field.flatten = true;
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    serde_field.default = Some(SerdeDefault::parse(&meta)?);
+                    Ok(())
+                } else {
+                    skip_meta(&meta)
+                }
+            })?;
+        }
+        Ok(serde_field)
+    }
+}
+
+/// The `#[serde(...)]` settings of a struct that affect its fields.
+#[derive(Default)]
+pub(crate) struct SerdeContainer {
+    pub rename_all: Option<RenameRule>,
+    pub default: Option<SerdeDefault>,
+}
+
+impl SerdeContainer {
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut container = SerdeContainer::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename_all") {
+                    if let Some(lit) = parse_serialize_name(&meta)? {
+                        container.rename_all = Some(RenameRule::from_lit(&lit)?);
+                    }
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    container.default = Some(SerdeDefault::parse(&meta)?);
+                    Ok(())
+                } else {
+                    skip_meta(&meta)
+                }
+            })?;
+        }
+        Ok(container)
+    }
+}
+
+/// Reads `key = "..."` or the `serialize` half of
+/// `key(serialize = "...", deserialize = "...")`.
+fn parse_serialize_name(meta: &ParseNestedMeta) -> Result<Option<LitStr>> {
+    if meta.input.peek(syn::Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+
+    let mut serialize = None;
+    meta.parse_nested_meta(|nested| {
+        if nested.path.is_ident("serialize") {
+            serialize = Some(nested.value()?.parse()?);
+        } else {
+            skip_meta(&nested)?;
+        }
+        Ok(())
+    })?;
+    Ok(serialize)
+}
+
+/// Consumes a setting the schema doesn't depend on.
+pub(crate) fn skip_meta(meta: &ParseNestedMeta) -> Result<()> {
+    if meta.input.peek(syn::Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| skip_meta(&nested))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+/// Yields the characters of raw SurrealQL with whether they are outside of
+/// quotes and brackets. Comments (`--`, `#`, `//` and `/* */`) are replaced
+/// by a single space, so quotes within them are not taken into account.
+fn scan(value: &str) -> Vec<(usize, char, bool)> {
+    let mut scanned = vec![];
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut chars = value.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            scanned.push((index, c, false));
+            continue;
+        }
+
+        let next = chars.peek().map(|&(_, next)| next);
+        match (c, next) {
+            ('-', Some('-')) | ('/', Some('/')) | ('#', _) => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+                scanned.push((index, ' ', depth == 0));
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = None;
+                for (_, c) in chars.by_ref() {
+                    if previous == Some('*') && c == '/' {
+                        break;
+                    }
+                    previous = Some(c);
+                }
+                scanned.push((index, ' ', depth == 0));
+            }
+            ('\'' | '"' | '`', _) => {
+                quote = Some(c);
+                scanned.push((index, c, false));
+            }
+            ('(' | '[' | '{', _) => {
+                depth += 1;
+                scanned.push((index, c, false));
+            }
+            (')' | ']' | '}', _) => {
+                depth = depth.saturating_sub(1);
+                scanned.push((index, c, false));
+            }
+            _ => scanned.push((index, c, depth == 0)),
+        }
+    }
+
+    scanned
+}
+
+/// Yields the byte offsets of `value` that are outside of quotes, brackets
+/// and comments.
+pub(crate) fn top_level_offsets(value: &str) -> Vec<usize> {
+    scan(value)
+        .into_iter()
+        .filter(|&(index, c, top_level)| top_level && value[index..].starts_with(c))
+        .map(|(index, _, _)| index)
+        .collect()
+}
+
+pub(crate) fn split_top_level(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+
+    for index in top_level_offsets(value) {
+        if value[index..].starts_with(separator) {
+            parts.push(&value[start..index]);
+            start = index + separator.len_utf8();
+        }
+    }
+    parts.push(&value[start..]);
+
+    parts
+}
+
+/// Splits raw SurrealQL into its statements, each ending with `;`. Comments
+/// are left out, along with the fragments holding nothing else.
+pub(crate) fn split_statements(value: &str) -> Vec<String> {
+    let mut statements = vec![];
+    let mut statement = String::new();
+    for (_, c, top_level) in scan(value) {
+        if top_level && c == ';' {
+            statements.push(std::mem::take(&mut statement));
+        } else {
+            statement.push(c);
+        }
+    }
+    statements.push(statement);
+
+    statements
+        .iter()
+        .map(|statement| statement.trim())
+        .filter(|statement| !statement.is_empty())
+        .map(|statement| format!("{};", statement))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_statements, split_top_level};
+
+    #[test]
+    fn splits_statements_outside_of_quotes_and_brackets() {
+        assert_eq!(
+            split_statements("DEFINE PARAM $sep VALUE ';'; DEFINE FUNCTION fn::a() { RETURN 1; }"),
+            [
+                "DEFINE PARAM $sep VALUE ';';",
+                "DEFINE FUNCTION fn::a() { RETURN 1; };"
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_comments() {
+        assert_eq!(
+            split_statements(
+                "-- don't touch\nDEFINE TABLE a; # it's\nDEFINE TABLE b /* it's; */; // it's done"
+            ),
+            ["DEFINE TABLE a;", "DEFINE TABLE b;"]
+        );
+        assert_eq!(split_statements("-- only a comment;"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn splits_around_comments() {
+        assert_eq!(split_top_level("a, -- b, c\nd", ','), ["a", " -- b, c\nd"]);
+    }
+}
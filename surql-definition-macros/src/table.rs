@@ -1,75 +1,314 @@
-use surql_definition_core::to_snake_case;
-use syn::{DeriveInput, Error};
+use surql_definition_core::{
+    AnalyzerDefinition, EventDefinition, PermissionDefinition, RecordIdKind, TableDefinition,
+};
+use syn::{ext::IdentExt, Data, DeriveInput, Error, GenericArgument, PathArguments, Type};
 
 use crate::{
-    field::FieldInfo,
-    permission::{format_permissions, parse_permissions_attributes, PermissionInfo},
+    access::AccessInfo,
+    analyzer::parse_analyzer_attribute,
+    doc::parse_doc_comment,
+    event::parse_event_attribute,
+    field::{FieldContext, FieldInfo, FlattenedField},
+    index::{IndexInfo, IndexKind},
+    permission::PermissionSet,
+    record_id::RecordIdInfo,
+    rename::{RenameRule, TableNaming},
+    scope::ScopeInfo,
+    serde_attr::{SerdeContainer, SerdeDefault},
+    statement::split_statements,
+    table_options::TableOptions,
+    type_conv::SurrealDBType,
 };
 
 pub(crate) struct TableInfo {
-    custom_query: Option<String>,
+    custom_query: Option<Vec<String>>,
+    queries_before: Vec<String>,
+    queries_after: Vec<String>,
     table_name: String,
-    permissions: Option<String>,
+    permissions: Option<PermissionDefinition>,
+    options: TableOptions,
+    comment: Option<String>,
     fields: Vec<FieldInfo>,
+    analyzers: Vec<AnalyzerDefinition>,
+    indexes: Vec<IndexInfo>,
+    events: Vec<EventDefinition>,
+    scope: Option<ScopeInfo>,
+    accesses: Vec<AccessInfo>,
+    record_id: Option<RecordIdInfo>,
+    flattened: Vec<FlattenedField>,
+    /// The types the `in` and `out` fields of a relation point to, whose
+    /// table names are only known to their `SurQLSchemaProducer`
+    /// implementation.
+    relation_types: (Option<Type>, Option<Type>),
+    /// From `#[surql(rename_all = "...")]`, overriding serde's.
+    rename_all: Option<RenameRule>,
+}
+
+pub(crate) enum FieldPart<'a> {
+    Field(&'a FieldInfo),
+    Flattened(&'a Type),
 }
 
 impl TableInfo {
     pub(crate) fn from_derive_input(input: &DeriveInput) -> Result<Self, Error> {
-        let (custom_query, table_name, permissions) = Self::parse_table_attributes(input)?;
-        let fields = FieldInfo::parse_fields(input)?;
+        let mut table_info = Self::parse_table_attributes(input)?;
 
-        Ok(TableInfo {
-            custom_query,
-            table_name,
-            permissions,
-            fields,
-        })
+        // The record id isn't a regular field, and relation tables define
+        // `in` and `out` themselves
+        let excluded: &[&str] = if table_info.options.is_relation() {
+            &["id", "in", "out"]
+        } else {
+            &["id"]
+        };
+        table_info.infer_relation_tables(input)?;
+        let container = SerdeContainer::from_attrs(&input.attrs)?;
+        let context = FieldContext {
+            rename_all: table_info.rename_all.or(container.rename_all),
+            serde_defaults: table_info.options.serde_defaults,
+            // Missing fields come from the `Default` implementation of the
+            // struct, which is out of reach of the macro
+            default: container.default.map(|default| match default {
+                SerdeDefault::Type => SerdeDefault::Custom {
+                    description: format!("the Default implementation of {}", input.ident),
+                    span: input.ident.span(),
+                },
+                custom => custom,
+            }),
+        };
+        (table_info.fields, table_info.flattened) =
+            FieldInfo::parse_fields(input, excluded, &context)?;
+        table_info.record_id = Self::parse_record_id(input)?;
+
+        let has_flattened = !table_info.flattened.is_empty();
+        // These schemas are built at runtime and cached in a static, which
+        // can't depend on generic parameters
+        if table_info.has_runtime_schema() && input.generics.type_params().next().is_some() {
+            let message = if has_flattened {
+                "Flattened fields are not supported on generic structs"
+            } else {
+                "Relation tables can't infer IN/OUT on generic structs. Consider setting IN/OUT explicitly in surql_table_options."
+            };
+            return Err(Error::new_spanned(&input.generics, message));
+        }
+
+        // Flattened fields aren't known here, so names can't be checked
+        if let Some(view) = table_info.options.view.as_ref().filter(|_| !has_flattened) {
+            view.check_fields(table_info.fields.iter().map(FieldInfo::name))?;
+        }
+
+        for access in &mut table_info.accesses {
+            access.check_mode(table_info.options.mode)?;
+            access.resolve_fields(&table_info.fields, has_flattened)?;
+        }
+
+        let mut known_fields = vec!["id", "in", "out"];
+        known_fields.extend(table_info.fields.iter().map(FieldInfo::name));
+        for index in table_info.indexes.iter().filter(|_| !has_flattened) {
+            index.check_fields(&known_fields)?;
+        }
+
+        let mut field_indexes = vec![];
+        let mut inline_analyzers = vec![];
+        for field in &table_info.fields {
+            for kind in field.indexes() {
+                if let IndexKind::Search(search) = kind {
+                    if let Some(analyzer) = &search.inline_analyzer {
+                        inline_analyzers.push((analyzer.clone(), search.span));
+                    }
+                }
+                field_indexes.push(IndexInfo::for_field(
+                    &table_info.table_name,
+                    field.name(),
+                    kind.clone(),
+                ));
+            }
+        }
+        for (analyzer, span) in inline_analyzers {
+            table_info.add_analyzer(analyzer, span)?;
+        }
+        // Generated names may collide with the name of a struct-level index
+        for index in field_indexes {
+            if let Some(existing) = table_info.indexes.iter().find(|i| i.name() == index.name()) {
+                return Err(Error::new(
+                    existing.span(),
+                    format!("Index {} is already defined by a field", index.name()),
+                ));
+            }
+            table_info.indexes.push(index);
+        }
+
+        Ok(table_info)
+    }
+
+    /// The statements of the schema query, leaving out flattened fields.
+    pub(crate) fn generate_define_statements(&self) -> Vec<String> {
+        self.to_definition().render_statements()
+    }
+
+    /// The statements checked by the query validation features. These parse
+    /// SurrealDB 2.x, so the `DEFINE SCOPE` statements of 1.x are left out.
+    pub(crate) fn generate_validated_statements(&self) -> Vec<String> {
+        let mut definition = self.to_definition();
+        definition.accesses.retain(|access| !access.legacy_scope);
+        definition.render_statements()
+    }
+
+    pub(crate) fn has_flattened_fields(&self) -> bool {
+        !self.flattened.is_empty()
+    }
+
+    pub(crate) fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Whether the schema depends on other `SurQLSchemaProducer`
+    /// implementations, through flattened fields or inferred relation tables.
+    pub(crate) fn has_runtime_schema(&self) -> bool {
+        let (in_type, out_type) = &self.relation_types;
+        self.has_flattened_fields() || in_type.is_some() || out_type.is_some()
     }
 
-    pub(crate) fn generate_define_query(&self) -> String {
-        self.custom_query.clone().unwrap_or_else(|| {
-            let fields_def = self
-                .fields
+    /// The types the `IN` and `OUT` tables are inferred from.
+    pub(crate) fn relation_types(&self) -> (Option<&Type>, Option<&Type>) {
+        let (in_type, out_type) = &self.relation_types;
+        (in_type.as_ref(), out_type.as_ref())
+    }
+
+    /// The structured schema of the table. Flattened fields are left out, as
+    /// they are only known once the `SurQLSchemaProducer` implementation of
+    /// their type runs.
+    pub(crate) fn to_definition(&self) -> TableDefinition {
+        TableDefinition {
+            name: self.table_name.clone(),
+            mode: self.options.mode,
+            drop: self.options.drop,
+            table_type: self.options.table_type.clone(),
+            view: self.options.view.as_ref().map(|view| view.query.clone()),
+            changefeed: self.options.changefeed.clone(),
+            permissions: self.permissions.clone(),
+            comment: self.comment.clone(),
+            fields: self.fields.iter().map(FieldInfo::to_definition).collect(),
+            analyzers: self.analyzers.clone(),
+            indexes: self.indexes.iter().map(IndexInfo::to_definition).collect(),
+            events: self.events.clone(),
+            accesses: self
+                .accesses
                 .iter()
-                .map(|field| field.generate_define_query(&self.table_name))
-                .collect::<Vec<_>>()
-                .join(" ");
+                .map(|access| access.definition.clone())
+                .collect(),
+            custom_query: self.custom_query.clone(),
+            queries_before: self.queries_before.clone(),
+            queries_after: self.queries_after.clone(),
+        }
+    }
 
-            let mut query = format!("DEFINE TABLE {};", self.table_name);
-            if let Some(perms) = &self.permissions {
-                query = format!("DEFINE TABLE {} PERMISSIONS {};", self.table_name, perms);
+    /// The fields of the struct in declaration order, flattened ones
+    /// included.
+    pub(crate) fn field_parts(&self) -> Vec<FieldPart<'_>> {
+        let mut parts = vec![];
+        let mut flattened = self.flattened.iter().peekable();
+        for (position, field) in self.fields.iter().enumerate() {
+            while let Some(f) = flattened.next_if(|f| f.position == position) {
+                parts.push(FieldPart::Flattened(&f.ty));
             }
-            format!("{} {}", query, fields_def)
-        })
+            parts.push(FieldPart::Field(field));
+        }
+        parts.extend(flattened.map(|f| FieldPart::Flattened(&f.ty)));
+        parts
+    }
+
+    /// Prefixes `query` with the namespace and database selection of the
+    /// table, if it declares a scope.
+    pub(crate) fn generate_scoped_query(&self, query: &str) -> Option<String> {
+        self.generate_scope_query()
+            .map(|scope| format!("{} {}", scope, query))
+    }
+
+    /// The namespace and database statements of the table, if it declares a
+    /// scope.
+    pub(crate) fn generate_scope_query(&self) -> Option<String> {
+        self.scope
+            .as_ref()
+            .map(|scope| scope.generate_define_query(self.options.mode))
     }
 
-    fn parse_table_attributes(
-        input: &DeriveInput,
-    ) -> Result<(Option<String>, String, Option<String>), Error> {
+    fn parse_table_attributes(input: &DeriveInput) -> Result<Self, Error> {
         let mut custom_query = None;
+        let mut queries_before = vec![];
+        let mut queries_after = vec![];
         let mut explicit_table_name = None;
-        let mut perms: Vec<PermissionInfo> = vec![];
+        let mut perms = PermissionSet::default();
+        let mut options = TableOptions::default();
+        let mut indexes = vec![];
+        let mut analyzers: Vec<AnalyzerDefinition> = vec![];
+        let mut events = vec![];
+        let mut scope = None;
+        let mut accesses = vec![];
+        let mut naming = TableNaming::default();
+        let mut rename_all = None;
 
         for attr in &input.attrs {
             if attr.path().is_ident("surql_query") {
                 let lit: syn::LitStr = attr.parse_args().map_err(|e| {
                     Error::new_spanned(attr, format!("Expected a string literal: {}", e))
                 })?;
-                custom_query = Some(lit.value());
+                custom_query = Some(split_statements(&lit.value()));
+            } else if attr.path().is_ident("surql_query_before")
+                || attr.path().is_ident("surql_query_after")
+            {
+                let lit: syn::LitStr = attr.parse_args().map_err(|e| {
+                    Error::new_spanned(attr, format!("Expected a string literal: {}", e))
+                })?;
+                if attr.path().is_ident("surql_query_before") {
+                    queries_before.extend(split_statements(&lit.value()));
+                } else {
+                    queries_after.extend(split_statements(&lit.value()));
+                }
             } else if attr.path().is_ident("surql_table") {
                 let lit: syn::LitStr = attr.parse_args().map_err(|e| {
                     Error::new_spanned(attr, format!("Expected a string literal: {}", e))
                 })?;
                 explicit_table_name = Some(lit.value());
+            } else if attr.path().is_ident("surql_analyzer") {
+                let analyzer = parse_analyzer_attribute(attr)?;
+                if analyzers.iter().any(|a| a.name == analyzer.name) {
+                    return Err(Error::new_spanned(attr, "Analyzer is already defined"));
+                }
+                analyzers.push(analyzer);
+            } else if attr.path().is_ident("surql_access") {
+                accesses.push(AccessInfo::parse_attribute(attr)?);
+            } else if attr.path().is_ident("surql_scope") {
+                scope = Some(ScopeInfo::parse_attribute(attr)?);
+            } else if attr.path().is_ident("surql_event") {
+                events.push(parse_event_attribute(attr)?);
+            } else if attr.path().is_ident("surql_index") {
+                let index = IndexInfo::parse_attribute(attr)?;
+                if indexes.iter().any(|i: &IndexInfo| i.name() == index.name()) {
+                    return Err(Error::new_spanned(attr, "Index is already defined"));
+                }
+                indexes.push(index);
+            } else if attr.path().is_ident("surql_table_options") {
+                options.parse_attribute(attr)?;
+            } else if attr.path().is_ident("surql") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename_all") {
+                        let lit: syn::LitStr = meta.value()?.parse()?;
+                        rename_all = Some(RenameRule::from_lit(&lit)?);
+                        Ok(())
+                    } else if naming.parse_nested_meta(&meta)? {
+                        Ok(())
+                    } else {
+                        Err(meta.error("Unrecognized surql attribute"))
+                    }
+                })?;
             } else if attr.path().is_ident("surql_table_permissions") {
                 attr.parse_nested_meta(|meta| {
-                    perms.push(parse_permissions_attributes(meta).map_err(|e| {
+                    perms.parse_nested_meta(meta).map_err(|e| {
                         Error::new_spanned(
                             attr,
                             format!("Failed to parse permissions attribute: {}", e),
                         )
-                    })?);
-                    Ok(())
+                    })
                 })
                 .map_err(|e| {
                     Error::new_spanned(
@@ -81,9 +320,126 @@ impl TableInfo {
         }
 
         let table_name =
-            explicit_table_name.unwrap_or_else(|| to_snake_case(&input.ident.to_string()));
-        let permissions = format_permissions(perms);
+            explicit_table_name.unwrap_or_else(|| naming.table_name(&input.ident.to_string()));
+        let permissions = perms
+            .build()
+            .map_err(|e| Error::new_spanned(&input.ident, e))?;
 
-        Ok((custom_query, table_name, permissions))
+        Ok(TableInfo {
+            custom_query,
+            queries_before,
+            queries_after,
+            table_name,
+            permissions,
+            options,
+            comment: parse_doc_comment(&input.attrs),
+            fields: vec![],
+            analyzers,
+            indexes,
+            events,
+            scope,
+            accesses,
+            record_id: None,
+            flattened: vec![],
+            relation_types: (None, None),
+            rename_all,
+        })
+    }
+
+    fn parse_record_id(input: &DeriveInput) -> Result<Option<RecordIdInfo>, Error> {
+        let Data::Struct(data_struct) = &input.data else {
+            return Ok(None);
+        };
+        match data_struct
+            .fields
+            .iter()
+            .find(|f| f.ident.as_ref().is_some_and(|ident| ident.unraw() == "id"))
+        {
+            Some(f) if !FieldInfo::is_skipped(f)? => RecordIdInfo::from_field(f).map(Some),
+            _ => Ok(None),
+        }
     }
+
+    /// The kind of record id, when the struct has an `id` field.
+    pub(crate) fn record_id_kind(&self) -> Option<RecordIdKind> {
+        self.record_id.as_ref().map(|record_id| record_id.kind)
+    }
+
+    /// Registers an analyzer declared inline on a search index. The same
+    /// analyzer may be shared by several fields as long as it is declared
+    /// identically.
+    fn add_analyzer(
+        &mut self,
+        analyzer: AnalyzerDefinition,
+        span: proc_macro2::Span,
+    ) -> Result<(), Error> {
+        match self.analyzers.iter().find(|a| a.name == analyzer.name) {
+            Some(existing) if *existing == analyzer => Ok(()),
+            Some(_) => Err(Error::new(
+                span,
+                format!("Analyzer {} is already defined differently", analyzer.name),
+            )),
+            None => {
+                self.analyzers.push(analyzer);
+                Ok(())
+            }
+        }
+    }
+
+    /// Records the types of the `in` and `out` fields of a relation, whose
+    /// tables fill `IN`/`OUT` unless they were given explicitly.
+    fn infer_relation_tables(&mut self, input: &DeriveInput) -> Result<(), Error> {
+        let Some((in_tables, out_tables)) = self.options.relation_tables_mut() else {
+            return Ok(());
+        };
+        let Data::Struct(data_struct) = &input.data else {
+            return Ok(());
+        };
+
+        let (in_type, out_type) = &mut self.relation_types;
+        for field in &data_struct.fields {
+            let Some(ident) = &field.ident else {
+                continue;
+            };
+            match ident.unraw().to_string().as_str() {
+                "in" if in_tables.is_none() => {
+                    *in_type = Some(relation_target_type(&field.ty)?);
+                }
+                "out" if out_tables.is_none() => {
+                    *out_type = Some(relation_target_type(&field.ty)?);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Resolves the type a relation endpoint points to: `User`, `Option<User>`
+/// and `RecordId<User>` all point to `User`. Other types, such as a `Thing`
+/// or a plain `String`, don't tell which table they belong to.
+fn relation_target_type(ty: &Type) -> Result<Type, Error> {
+    if let Type::Path(type_path) = ty {
+        if let Some(segment) = type_path.path.segments.last() {
+            let inner_ty = match &segment.arguments {
+                PathArguments::AngleBracketed(args) => match args.args.first() {
+                    Some(GenericArgument::Type(inner_ty)) => Some(inner_ty),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match (segment.ident.to_string().as_str(), inner_ty) {
+                ("Option" | "RecordId", Some(inner_ty)) => return relation_target_type(inner_ty),
+                ("Thing" | "RecordId", _) => {}
+                (_, None) if SurrealDBType::from_type(ty).is_err() => return Ok(ty.clone()),
+                _ => {}
+            }
+        }
+    }
+
+    Err(Error::new_spanned(
+        ty,
+        "Unable to infer the relation table from this type. Consider setting IN/OUT explicitly in surql_table_options.",
+    ))
 }
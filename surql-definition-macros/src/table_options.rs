@@ -0,0 +1,194 @@
+pub(crate) use surql_definition_core::DefinitionMode;
+use surql_definition_core::{Changefeed, TableType};
+use syn::{meta::ParseNestedMeta, Attribute, Error, Lit, Result};
+
+use crate::{duration::validate_duration, view::ViewInfo};
+
+/// Handles the `IF_NOT_EXISTS` and `OVERWRITE` keys. Returns `false` when
+/// the key isn't a definition mode.
+pub(crate) fn parse_definition_mode(
+    mode: &mut DefinitionMode,
+    meta: &ParseNestedMeta,
+) -> Result<bool> {
+    let parsed = if meta.path.is_ident("IF_NOT_EXISTS") {
+        DefinitionMode::IfNotExists
+    } else if meta.path.is_ident("OVERWRITE") {
+        DefinitionMode::Overwrite
+    } else {
+        return Ok(false);
+    };
+    if *mode != DefinitionMode::Default {
+        return Err(meta.error("IF_NOT_EXISTS and OVERWRITE are mutually exclusive"));
+    }
+    *mode = parsed;
+    Ok(true)
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct TableOptions {
+    pub mode: DefinitionMode,
+    pub drop: bool,
+    pub table_type: Option<TableType>,
+    pub view: Option<ViewInfo>,
+    pub changefeed: Option<Changefeed>,
+    pub serde_defaults: bool,
+}
+
+impl TableOptions {
+    pub(crate) fn parse_attribute(&mut self, attr: &Attribute) -> Result<()> {
+        let mut in_tables = None;
+        let mut out_tables = None;
+        let mut enforced = false;
+        let mut relation = false;
+        let mut include_original = false;
+
+        attr.parse_nested_meta(|meta| {
+            let attribute_name = meta
+                .path
+                .get_ident()
+                .map(|ident| ident.to_string())
+                .unwrap_or_default();
+            match attribute_name.as_str() {
+                "IF_NOT_EXISTS" | "OVERWRITE" => {
+                    parse_definition_mode(&mut self.mode, &meta)?;
+                    Ok(())
+                }
+                "DROP" => {
+                    self.drop = true;
+                    Ok(())
+                }
+                "ANY" => {
+                    self.set_table_type(TableType::Any, &meta)?;
+                    Ok(())
+                }
+                "NORMAL" => {
+                    self.set_table_type(TableType::Normal, &meta)?;
+                    Ok(())
+                }
+                "RELATION" => {
+                    relation = true;
+                    Ok(())
+                }
+                "ENFORCED" => {
+                    enforced = true;
+                    Ok(())
+                }
+                "IN" | "OUT" => {
+                    let lit: Lit = meta.value()?.parse()?;
+                    let tables = match lit {
+                        Lit::Str(lit_str) => lit_str.value(),
+                        _ => {
+                            return Err(
+                                meta.error(format!("Expected a string for {}", attribute_name))
+                            )
+                        }
+                    };
+                    match attribute_name.as_str() {
+                        "IN" => in_tables = Some(tables),
+                        "OUT" => out_tables = Some(tables),
+                        _ => unreachable!(),
+                    }
+                    Ok(())
+                }
+                "AS" => {
+                    if self.view.is_some() {
+                        return Err(meta.error("View query is already set"));
+                    }
+                    let lit: Lit = meta.value()?.parse()?;
+                    match lit {
+                        Lit::Str(lit_str) => {
+                            self.view = Some(ViewInfo::from_lit(lit_str)?);
+                            Ok(())
+                        }
+                        _ => Err(meta.error("Expected a string for AS")),
+                    }
+                }
+                "CHANGEFEED" => {
+                    let lit: Lit = meta.value()?.parse()?;
+                    match lit {
+                        Lit::Str(lit_str) => {
+                            let duration = lit_str.value();
+                            validate_duration(&duration)
+                                .map_err(|e| Error::new_spanned(&lit_str, e))?;
+                            self.changefeed = Some(Changefeed {
+                                duration,
+                                include_original: false,
+                            });
+                            Ok(())
+                        }
+                        _ => Err(meta.error("Expected a string for CHANGEFEED")),
+                    }
+                }
+                "INCLUDE_ORIGINAL" => {
+                    include_original = true;
+                    Ok(())
+                }
+                "SERDE_DEFAULTS" => {
+                    self.serde_defaults = true;
+                    Ok(())
+                }
+                _ => Err(meta.error("Unrecognized table option")),
+            }
+        })?;
+
+        if include_original {
+            match &mut self.changefeed {
+                Some(changefeed) => changefeed.include_original = true,
+                None => {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "INCLUDE_ORIGINAL requires a CHANGEFEED duration",
+                    ))
+                }
+            }
+        }
+
+        if relation {
+            if self.table_type.is_some() {
+                return Err(Error::new_spanned(attr, "Table type is already set"));
+            }
+            self.table_type = Some(TableType::Relation {
+                in_tables,
+                out_tables,
+                enforced,
+            });
+        } else if enforced || in_tables.is_some() || out_tables.is_some() {
+            return Err(Error::new_spanned(
+                attr,
+                "ENFORCED, IN and OUT can only be used on RELATION tables",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn set_table_type(
+        &mut self,
+        table_type: TableType,
+        meta: &syn::meta::ParseNestedMeta,
+    ) -> Result<()> {
+        if self.table_type.is_some() {
+            return Err(meta.error("Table type is already set"));
+        }
+        self.table_type = Some(table_type);
+        Ok(())
+    }
+
+    pub(crate) fn is_relation(&self) -> bool {
+        matches!(self.table_type, Some(TableType::Relation { .. }))
+    }
+
+    /// The `IN` and `OUT` tables of a relation table.
+    pub(crate) fn relation_tables_mut(
+        &mut self,
+    ) -> Option<(&mut Option<String>, &mut Option<String>)> {
+        match &mut self.table_type {
+            Some(TableType::Relation {
+                in_tables,
+                out_tables,
+                ..
+            }) => Some((in_tables, out_tables)),
+            _ => None,
+        }
+    }
+}
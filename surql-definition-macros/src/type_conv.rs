@@ -1,7 +1,8 @@
 use quote::ToTokens;
 use std::fmt;
+use surql_definition_core::ToSurQLLiteral;
 use syn::{Error, Result};
-use syn::{GenericArgument, Lit, PathArguments, Type};
+use syn::{Expr, ExprLit, GenericArgument, Lit, PathArguments, Type};
 
 pub(crate) fn format_lit_as_expr(lit: Lit) -> String {
     match lit {
@@ -11,10 +12,82 @@ pub(crate) fn format_lit_as_expr(lit: Lit) -> String {
     }
 }
 
+/// Converts a constant Rust expression (literals, negated numbers, arrays,
+/// tuples and `Option`) into a SurrealQL literal.
+pub(crate) fn format_expr_as_literal(expr: &Expr) -> Result<String> {
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => match lit {
+            Lit::Str(lit_str) => Ok(lit_str.value().to_surql_literal()),
+            Lit::Char(lit_char) => Ok(lit_char.value().to_surql_literal()),
+            Lit::Bool(lit_bool) => Ok(lit_bool.value().to_string()),
+            Lit::Int(lit_int) => Ok(lit_int.base10_digits().to_string()),
+            Lit::Float(lit_float) => {
+                let digits = lit_float.base10_digits();
+                Ok(if digits.contains(['.', 'e', 'E']) {
+                    digits.to_string()
+                } else {
+                    format!("{}.0", digits)
+                })
+            }
+            _ => Err(Error::new_spanned(lit, "Unsupported literal")),
+        },
+        Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Neg(_)) => match &*unary.expr {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(_) | Lit::Float(_),
+                ..
+            }) => Ok(format!("-{}", format_expr_as_literal(&unary.expr)?)),
+            _ => Err(Error::new_spanned(expr, "Only numbers can be negated")),
+        },
+        Expr::Reference(reference) => format_expr_as_literal(&reference.expr),
+        Expr::Group(group) => format_expr_as_literal(&group.expr),
+        Expr::Paren(paren) => format_expr_as_literal(&paren.expr),
+        Expr::Array(array) => Ok(format!(
+            "[{}]",
+            array
+                .elems
+                .iter()
+                .map(format_expr_as_literal)
+                .collect::<Result<Vec<_>>>()?
+                .join(", ")
+        )),
+        Expr::Tuple(tuple) => Ok(format!(
+            "[{}]",
+            tuple
+                .elems
+                .iter()
+                .map(format_expr_as_literal)
+                .collect::<Result<Vec<_>>>()?
+                .join(", ")
+        )),
+        Expr::Path(path) if path.path.is_ident("None") => Ok("NONE".to_string()),
+        Expr::Call(call)
+            if matches!(&*call.func, Expr::Path(path) if path.path.is_ident("Some"))
+                && call.args.len() == 1 =>
+        {
+            format_expr_as_literal(&call.args[0])
+        }
+        _ => Err(Error::new_spanned(
+            expr,
+            "Unsupported value, expected a literal, an array, a tuple or an Option",
+        )),
+    }
+}
+
+/// Whether `ty` is `char`, possibly behind references.
+pub(crate) fn is_char(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path.path.is_ident("char"),
+        Type::Reference(type_reference) => is_char(&type_reference.elem),
+        _ => false,
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct SurrealDBType {
     pub name: String,
     pub inner: Option<Box<SurrealDBType>>,
+    /// Maximum length of an `array`, as in `array<float, 3>`.
+    pub len: Option<usize>,
 }
 
 impl SurrealDBType {
@@ -22,9 +95,37 @@ impl SurrealDBType {
         Self {
             name: name.to_string(),
             inner: inner.map(Box::new),
+            len: None,
+        }
+    }
+
+    pub fn with_len(mut self, len: Option<usize>) -> Self {
+        self.len = len;
+        self
+    }
+
+    /// The element type of an array, looking through `option`.
+    pub fn array_element(&self) -> Option<&SurrealDBType> {
+        match (self.name.as_str(), &self.inner) {
+            ("array", Some(inner)) => Some(inner),
+            ("option", Some(inner)) => inner.array_element(),
+            _ => None,
+        }
+    }
+
+    /// The maximum length of an array, looking through `option`.
+    pub fn array_len(&self) -> Option<usize> {
+        match (self.name.as_str(), &self.inner) {
+            ("array", _) => self.len,
+            ("option", Some(inner)) => inner.array_len(),
+            _ => None,
         }
     }
 
+    pub fn is_numeric(&self) -> bool {
+        matches!(self.name.as_str(), "int" | "float" | "number" | "decimal")
+    }
+
     pub fn from_string(value: &str) -> Self {
         match value {
             "int" => SurrealDBType::new("int", None),
@@ -36,7 +137,16 @@ impl SurrealDBType {
             _ => {
                 if value.starts_with("array<") && value.ends_with(">") {
                     let inner = &value[6..value.len() - 1];
-                    SurrealDBType::new("array", Some(SurrealDBType::from_string(inner)))
+                    match inner.rsplit_once(',') {
+                        Some((elem, len)) if len.trim().parse::<usize>().is_ok() => {
+                            SurrealDBType::new(
+                                "array",
+                                Some(SurrealDBType::from_string(elem.trim())),
+                            )
+                            .with_len(len.trim().parse().ok())
+                        }
+                        _ => SurrealDBType::new("array", Some(SurrealDBType::from_string(inner))),
+                    }
                 } else if value.starts_with("option<") && value.ends_with(">") {
                     let inner = &value[7..value.len() - 1];
                     SurrealDBType::new("option", Some(SurrealDBType::from_string(inner)))
@@ -47,6 +157,30 @@ impl SurrealDBType {
         }
     }
 
+    /// The SurrealQL literal of `Default::default()` for the Rust types
+    /// mapped to this type.
+    pub fn default_literal(&self) -> Option<String> {
+        let literal = match self.name.as_str() {
+            "int" | "number" => "0",
+            "float" => "0.0",
+            "bool" => "false",
+            "string" => "\"\"",
+            "option" => "NONE",
+            "array" if self.len.is_none() => "[]",
+            _ => return None,
+        };
+        Some(literal.to_string())
+    }
+
+    /// Whether the type holds text, possibly wrapped in `option` or `array`.
+    pub fn is_string_like(&self) -> bool {
+        match (self.name.as_str(), &self.inner) {
+            ("string", _) => true,
+            ("option" | "array", Some(inner)) => inner.is_string_like(),
+            _ => false,
+        }
+    }
+
     pub fn from_type(ty: &Type) -> Result<Self> {
         match ty {
             Type::Path(type_path) => {
@@ -59,7 +193,7 @@ impl SurrealDBType {
                         "f32" | "f64" => Ok(SurrealDBType::new("float", None)),
                         "bool" => Ok(SurrealDBType::new("bool", None)),
                         "String" => Ok(SurrealDBType::new("string", None)),
-                        "char" => Ok(SurrealDBType::new("string", None)),
+                        "char" | "str" => Ok(SurrealDBType::new("string", None)),
                         "Option" => {
                             if let PathArguments::AngleBracketed(args) = &segment.arguments {
                                 if let Some(GenericArgument::Type(inner_ty)) = args.args.iter().next() {
@@ -84,21 +218,32 @@ impl SurrealDBType {
                     Err(Error::new_spanned(type_path, "Path segment is missing"))
                 }
             }
+            Type::Array(type_array) => {
+                let inner = SurrealDBType::from_type(&type_array.elem)?;
+                let len = match &type_array.len {
+                    Expr::Lit(ExprLit { lit: Lit::Int(lit_int), .. }) => lit_int.base10_parse().ok(),
+                    _ => None,
+                };
+                Ok(SurrealDBType::new("array", Some(inner)).with_len(len))
+            }
+            Type::Slice(type_slice) => {
+                let inner = SurrealDBType::from_type(&type_slice.elem)?;
+                Ok(SurrealDBType::new("array", Some(inner)))
+            }
+            Type::Reference(type_reference) => SurrealDBType::from_type(&type_reference.elem),
             _ => Err(Error::new_spanned(ty, format!("Unsupported type: {}. Consider defining this type explicitly using the TYPE statement.", ty.to_token_stream()))),
         }
     }
-
-    pub fn to_string(&self) -> String {
-        if let Some(inner) = &self.inner {
-            format!("{}<{}>", self.name, inner)
-        } else {
-            self.name.clone()
-        }
-    }
 }
 
 impl fmt::Display for SurrealDBType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        if let (Some(inner), Some(len)) = (&self.inner, self.len) {
+            write!(f, "{}<{}, {}>", self.name, inner, len)
+        } else if let Some(inner) = &self.inner {
+            write!(f, "{}<{}>", self.name, inner)
+        } else {
+            write!(f, "{}", self.name)
+        }
     }
 }
@@ -0,0 +1,148 @@
+use syn::{Error, LitStr, Result};
+
+use crate::statement::{split_top_level, top_level_offsets};
+
+/// A pre-computed table declared through `surql_table_options(AS = "SELECT ...")`.
+#[derive(Clone)]
+pub(crate) struct ViewInfo {
+    pub query: String,
+    pub projections: Option<Vec<String>>,
+    lit: LitStr,
+}
+
+impl ViewInfo {
+    pub(crate) fn from_lit(lit: LitStr) -> Result<Self> {
+        let query = lit.value().trim().trim_end_matches(';').trim().to_string();
+
+        if !starts_with_keyword(&query, "SELECT") {
+            return Err(Error::new_spanned(
+                &lit,
+                "View tables must be defined with a SELECT statement",
+            ));
+        }
+
+        let from = find_top_level_keyword(&query, "FROM")
+            .ok_or_else(|| Error::new_spanned(&lit, "View query is missing a FROM clause"))?;
+        let projections = parse_projections(&query[6..from]).map_err(|e| {
+            Error::new_spanned(&lit, format!("Unable to check view projections: {}", e))
+        })?;
+
+        Ok(ViewInfo {
+            query,
+            projections,
+            lit,
+        })
+    }
+
+    /// Ensures the struct fields and the projected names of the view line up.
+    /// `id` is always available on a view and is not required in the projection.
+    pub(crate) fn check_fields<'a>(&self, fields: impl Iterator<Item = &'a str>) -> Result<()> {
+        let Some(projections) = &self.projections else {
+            return Ok(());
+        };
+
+        let fields: Vec<&str> = fields.filter(|name| *name != "id").collect();
+        let missing: Vec<&str> = fields
+            .iter()
+            .filter(|name| !projections.iter().any(|p| p == *name))
+            .copied()
+            .collect();
+        let extra: Vec<&str> = projections
+            .iter()
+            .filter(|p| !fields.contains(&p.as_str()))
+            .map(String::as_str)
+            .collect();
+
+        let mut errors = vec![];
+        if !missing.is_empty() {
+            errors.push(format!(
+                "fields not projected by the view query: {}",
+                missing.join(", ")
+            ));
+        }
+        if !extra.is_empty() {
+            errors.push(format!(
+                "projections without a matching field: {}",
+                extra.join(", ")
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::new_spanned(
+                &self.lit,
+                format!("View does not match the struct: {}", errors.join("; ")),
+            ))
+        }
+    }
+}
+
+/// Returns the output names of a projection list, or `None` for a wildcard
+/// projection which can't be checked.
+fn parse_projections(projection: &str) -> std::result::Result<Option<Vec<String>>, String> {
+    let projection = projection.trim();
+    if starts_with_keyword(projection, "VALUE") {
+        return Err("SELECT VALUE does not produce named fields".to_string());
+    }
+
+    let mut names = vec![];
+    for expr in split_top_level(projection, ',') {
+        let expr = expr.trim();
+        if expr == "*" {
+            return Ok(None);
+        }
+
+        let name = match find_last_top_level_keyword(expr, "AS") {
+            Some(index) => expr[index + 2..].trim(),
+            None => {
+                let name = expr.split(['.', '[']).next().unwrap_or_default().trim();
+                if !is_identifier(name.trim_matches('`')) {
+                    return Err(format!("`{}` needs an alias", expr));
+                }
+                name
+            }
+        };
+
+        names.push(name.trim_matches('`').to_string());
+    }
+
+    Ok(Some(names))
+}
+
+fn is_identifier(value: &str) -> bool {
+    !value.is_empty() && value.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn starts_with_keyword(value: &str, keyword: &str) -> bool {
+    value.len() >= keyword.len()
+        && value[..keyword.len()].eq_ignore_ascii_case(keyword)
+        && value[keyword.len()..]
+            .chars()
+            .next()
+            .is_none_or(char::is_whitespace)
+}
+
+fn keyword_offsets(value: &str, keyword: &str) -> Vec<usize> {
+    let bytes = value.as_bytes();
+
+    top_level_offsets(value)
+        .into_iter()
+        .filter(|&index| {
+            let end = index + keyword.len();
+            end <= value.len()
+                && value.is_char_boundary(end)
+                && value[index..end].eq_ignore_ascii_case(keyword)
+                && (index == 0 || bytes[index - 1].is_ascii_whitespace())
+                && (end == value.len() || bytes[end].is_ascii_whitespace())
+        })
+        .collect()
+}
+
+fn find_top_level_keyword(value: &str, keyword: &str) -> Option<usize> {
+    keyword_offsets(value, keyword).into_iter().next()
+}
+
+fn find_last_top_level_keyword(value: &str, keyword: &str) -> Option<usize> {
+    keyword_offsets(value, keyword).into_iter().last()
+}
@@ -0,0 +1,59 @@
+//! Run with `cargo test --features compile_query_validation,runtime_query_validation`.
+#![cfg(any(
+    feature = "compile_query_validation",
+    feature = "runtime_query_validation"
+))]
+
+#[cfg(test)]
+#[allow(dead_code)]
+mod tests {
+
+    use surql_definition_core::SurQLSchemaProducer;
+    use surql_definition_macros::{surql_param, SurQLDefinition};
+
+    #[test]
+    fn test_surrealdb_2_syntax() {
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(OVERWRITE)]
+        #[surql_access(name = "account", identifier = "email", TOKEN = "15m", SESSION = "12h")]
+        #[surql_scope(ns = "app", db = "main")]
+        struct Account {
+            #[surql_field(UNIQUE)]
+            email: String,
+            #[surql_field(PASSWORD)]
+            password: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(IF_NOT_EXISTS, RELATION, IN = "account", OUT = "account", ENFORCED)]
+        struct Follows {
+            r#in: String,
+            out: String,
+            since: String,
+        }
+
+        #[surql_param(OVERWRITE)]
+        const MAX_FOLLOWS: i64 = 5000;
+
+        assert!(Account::schema_query().contains("DEFINE ACCESS OVERWRITE account"));
+        assert!(Account::scoped_schema_query().starts_with("DEFINE NAMESPACE OVERWRITE app;"));
+        assert!(Follows::schema_query().contains("ENFORCED"));
+        assert_eq!(
+            MAX_FOLLOWS_SURQL,
+            "DEFINE PARAM OVERWRITE $max_follows VALUE 5000;"
+        );
+    }
+
+    #[test]
+    fn test_legacy_scope_is_not_validated() {
+        #[derive(SurQLDefinition)]
+        #[surql_access(name = "account", identifier = "email", SCOPE)]
+        struct LegacyAccount {
+            email: String,
+            #[surql_field(PASSWORD)]
+            pass: String,
+        }
+
+        assert!(LegacyAccount::schema_query().contains("DEFINE SCOPE account"));
+    }
+}
@@ -2,8 +2,11 @@
 #[allow(dead_code)]
 mod tests {
 
-    use surql_definition_core::SurQLSchemaProducer;
-    use surql_definition_macros::SurQLDefinition;
+    use surql_definition_core::{
+        Bm25, DefinitionMode, IndexDefinition, IndexKind, PermissionDefinition,
+        PermissionOperation, PermissionRule, RecordIdKind, SurQLSchemaProducer, TableType,
+    };
+    use surql_definition_macros::{surql_function, surql_param, SurQLDefinition};
 
     #[test]
     fn test_simple_auto() {
@@ -216,6 +219,996 @@ mod tests {
         assert_eq!(ReadonlyStruct::schema_query(), expected);
     }
 
+    #[test]
+    fn test_relation_table() {
+        #[derive(SurQLDefinition)]
+        struct User {
+            name: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Post {
+            title: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(RELATION, ENFORCED)]
+        struct Likes {
+            r#in: User,
+            out: Post,
+            created_at: String,
+        }
+
+        assert_eq!(
+            Likes::schema_query(),
+            "DEFINE TABLE likes TYPE RELATION IN user OUT post ENFORCED; DEFINE FIELD created_at ON likes TYPE string;"
+        );
+        assert_eq!(
+            Likes::schema_statements(),
+            [
+                "DEFINE TABLE likes TYPE RELATION IN user OUT post ENFORCED;",
+                "DEFINE FIELD created_at ON likes TYPE string;",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relation_table_naming() {
+        struct RecordId<T>(T);
+
+        #[derive(SurQLDefinition)]
+        #[surql_table("users")]
+        struct User {
+            name: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql(plural, prefix = "app_")]
+        struct BlogPost {
+            title: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(RELATION)]
+        struct Wrote {
+            r#in: RecordId<User>,
+            out: Option<BlogPost>,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(RELATION, OUT = "comment")]
+        struct Reviewed {
+            r#in: User,
+            out: String,
+        }
+
+        assert_eq!(
+            Wrote::schema_query(),
+            "DEFINE TABLE wrote TYPE RELATION IN users OUT app_blog_posts;"
+        );
+        assert_eq!(
+            Reviewed::schema_query(),
+            "DEFINE TABLE reviewed TYPE RELATION IN users OUT comment;"
+        );
+    }
+
+    #[test]
+    fn test_relation_table_explicit_endpoints() {
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(RELATION, IN = "user", OUT = "post | comment")]
+        struct Reacted {
+            r#in: String,
+            out: String,
+        }
+
+        assert_eq!(
+            Reacted::schema_query(),
+            "DEFINE TABLE reacted TYPE RELATION IN user OUT post | comment;"
+        );
+    }
+
+    #[test]
+    fn test_view_table() {
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(
+            AS = "SELECT count() AS total, math::sum(amount) AS revenue, customer FROM order GROUP BY customer"
+        )]
+        struct CustomerRevenue {
+            total: i64,
+            revenue: f64,
+            customer: String,
+        }
+
+        assert_eq!(
+            CustomerRevenue::schema_query(),
+            "DEFINE TABLE customer_revenue AS SELECT count() AS total, math::sum(amount) AS revenue, customer FROM order GROUP BY customer;"
+        );
+    }
+
+    #[test]
+    fn test_drop_table() {
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(DROP)]
+        #[surql_table_permissions(CREATE = "WHERE $auth.id != NONE")]
+        struct PageView {
+            path: String,
+        }
+
+        assert_eq!(
+            PageView::schema_query(),
+            "DEFINE TABLE page_view DROP PERMISSIONS FOR create WHERE $auth.id != NONE; DEFINE FIELD path ON page_view TYPE string;"
+        );
+    }
+
+    #[test]
+    fn test_changefeed_table() {
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(CHANGEFEED = "7d12h", INCLUDE_ORIGINAL)]
+        struct Invoice {
+            amount: f64,
+        }
+
+        assert_eq!(
+            Invoice::schema_query(),
+            "DEFINE TABLE invoice CHANGEFEED 7d12h INCLUDE ORIGINAL; DEFINE FIELD amount ON invoice TYPE float;"
+        );
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        /// Registered customers.
+        ///
+        /// Imported nightly from the "CRM" export.
+        #[derive(SurQLDefinition)]
+        struct Customer {
+            /// Primary contact e-mail, `\` escaped.
+            email: String,
+            // Not a doc comment
+            name: String,
+        }
+
+        assert_eq!(
+            Customer::schema_query(),
+            r#"DEFINE TABLE customer COMMENT "Registered customers.\n\nImported nightly from the \"CRM\" export."; DEFINE FIELD email ON customer TYPE string COMMENT "Primary contact e-mail, `\\` escaped."; DEFINE FIELD name ON customer TYPE string;"#
+        );
+    }
+
+    #[test]
+    fn test_definition_modes() {
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(IF_NOT_EXISTS)]
+        struct Account {
+            name: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(OVERWRITE)]
+        struct Session {
+            token: String,
+        }
+
+        assert_eq!(
+            Account::schema_query(),
+            "DEFINE TABLE IF NOT EXISTS account; DEFINE FIELD IF NOT EXISTS name ON account TYPE string;"
+        );
+        assert_eq!(
+            Session::schema_query(),
+            "DEFINE TABLE OVERWRITE session; DEFINE FIELD OVERWRITE token ON session TYPE string;"
+        );
+    }
+
+    #[test]
+    fn test_permissions() {
+        #[derive(SurQLDefinition)]
+        #[surql_table_permissions(
+            FOR(select, update) = "WHERE owner = $auth.id",
+            DELETE = "WHERE $auth.admin = true"
+        )]
+        struct Document {
+            #[surql_field_permissions(NONE)]
+            secret: String,
+            #[surql_field_permissions(FULL)]
+            title: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql_table_permissions(NONE)]
+        struct Internal {
+            value: i32,
+        }
+
+        assert_eq!(
+            Document::schema_query(),
+            "DEFINE TABLE document PERMISSIONS FOR select, update WHERE owner = $auth.id FOR delete WHERE $auth.admin = true; DEFINE FIELD secret ON document TYPE string PERMISSIONS NONE; DEFINE FIELD title ON document TYPE string PERMISSIONS FULL;"
+        );
+        assert_eq!(
+            Internal::schema_query(),
+            "DEFINE TABLE internal PERMISSIONS NONE; DEFINE FIELD value ON internal TYPE int;"
+        );
+    }
+
+    #[test]
+    fn test_merged_permissions() {
+        #[derive(SurQLDefinition)]
+        #[surql_table_permissions(MERGE = "OR", SELECT = "WHERE published = true")]
+        #[surql_table_permissions(
+            FOR(select, update) = "WHERE owner = $auth.id",
+            DELETE = "WHERE owner = $auth.id"
+        )]
+        struct Article {
+            #[surql_field_permissions(MERGE = "AND", UPDATE = "WHERE $auth.verified = true")]
+            #[surql_field_permissions(UPDATE = "WHERE $auth.admin = true", SELECT = "FULL")]
+            body: String,
+        }
+
+        assert_eq!(
+            Article::schema_query(),
+            "DEFINE TABLE article PERMISSIONS FOR select WHERE (published = true) OR (owner = $auth.id) FOR update, delete WHERE owner = $auth.id; DEFINE FIELD body ON article TYPE string PERMISSIONS FOR update WHERE ($auth.verified = true) AND ($auth.admin = true) FOR select FULL;"
+        );
+    }
+
+    #[test]
+    fn test_indexes() {
+        #[derive(SurQLDefinition)]
+        #[surql_index(name = "author_slug", fields = ["author", "slug"], UNIQUE)]
+        #[surql_index(name = "by_date", fields = [published_at])]
+        struct BlogPost {
+            author: String,
+            slug: String,
+            #[surql_field(INDEX)]
+            category: String,
+            #[surql_field(UNIQUE)]
+            permalink: String,
+            published_at: String,
+        }
+
+        assert_eq!(
+            BlogPost::schema_query(),
+            "DEFINE TABLE blog_post; DEFINE FIELD author ON blog_post TYPE string; DEFINE FIELD slug ON blog_post TYPE string; DEFINE FIELD category ON blog_post TYPE string; DEFINE FIELD permalink ON blog_post TYPE string; DEFINE FIELD published_at ON blog_post TYPE string; DEFINE INDEX author_slug ON TABLE blog_post FIELDS author, slug UNIQUE; DEFINE INDEX by_date ON TABLE blog_post FIELDS published_at; DEFINE INDEX blog_post_category_idx ON TABLE blog_post FIELDS category; DEFINE INDEX blog_post_permalink_idx ON TABLE blog_post FIELDS permalink UNIQUE;"
+        );
+    }
+
+    #[test]
+    fn test_search_indexes() {
+        #[derive(SurQLDefinition)]
+        #[surql_analyzer(
+            name = "english",
+            tokenizers = ["blank", "class"],
+            filters = ["lowercase", "snowball(english)"]
+        )]
+        struct Recipe {
+            #[surql_search(analyzer = "english", BM25, HIGHLIGHTS)]
+            title: String,
+            #[surql_search(analyzer = "autocomplete", tokenizers = ["blank"], filters = ["lowercase", "edgengram(2, 10)"], BM25(1.2, 0.75))]
+            tags: Vec<String>,
+        }
+
+        assert_eq!(
+            Recipe::schema_query(),
+            "DEFINE TABLE recipe; DEFINE FIELD title ON recipe TYPE string; DEFINE FIELD tags ON recipe TYPE array<string>; DEFINE ANALYZER english TOKENIZERS blank,class FILTERS lowercase,snowball(english); DEFINE ANALYZER autocomplete TOKENIZERS blank FILTERS lowercase,edgengram(2,10); DEFINE INDEX recipe_title_search ON TABLE recipe FIELDS title SEARCH ANALYZER english BM25 HIGHLIGHTS; DEFINE INDEX recipe_tags_search ON TABLE recipe FIELDS tags SEARCH ANALYZER autocomplete BM25(1.2,0.75);"
+        );
+    }
+
+    #[test]
+    fn test_vector_indexes() {
+        #[derive(SurQLDefinition)]
+        struct Chunk {
+            #[surql_vector(MTREE, DIST = "cosine", TYPE = "F32")]
+            embedding: [f32; 4],
+            #[surql_vector(HNSW, DIMENSION = 768, DIST = "EUCLIDEAN", EFC = 150, M = 12)]
+            large_embedding: Vec<f32>,
+        }
+
+        assert_eq!(
+            Chunk::schema_query(),
+            "DEFINE TABLE chunk; DEFINE FIELD embedding ON chunk TYPE array<float, 4>; DEFINE FIELD large_embedding ON chunk TYPE array<float>; DEFINE INDEX chunk_embedding_vector ON TABLE chunk FIELDS embedding MTREE DIMENSION 4 TYPE F32 DIST COSINE; DEFINE INDEX chunk_large_embedding_vector ON TABLE chunk FIELDS large_embedding HNSW DIMENSION 768 DIST EUCLIDEAN EFC 150 M 12;"
+        );
+    }
+
+    #[test]
+    fn test_events() {
+        #[derive(SurQLDefinition)]
+        #[surql_event(
+            name = "email_changed",
+            WHEN = "$before.email != $after.email",
+            THEN = "CREATE audit SET user = $value.id, old = $before.email, new = $after.email"
+        )]
+        #[surql_event(name = "touch", THEN = "{ UPDATE stats SET users += 1; }")]
+        struct Member {
+            email: String,
+        }
+
+        assert_eq!(
+            Member::schema_query(),
+            "DEFINE TABLE member; DEFINE FIELD email ON member TYPE string; DEFINE EVENT email_changed ON TABLE member WHEN $before.email != $after.email THEN (CREATE audit SET user = $value.id, old = $before.email, new = $after.email); DEFINE EVENT touch ON TABLE member THEN { UPDATE stats SET users += 1; };"
+        );
+    }
+
+    #[test]
+    fn test_custom_queries_around_schema() {
+        #[derive(SurQLDefinition)]
+        #[surql_query_before("DEFINE PARAM $max_tags VALUE 10")]
+        #[surql_query_after("DEFINE FIELD tags.* ON tagged TYPE string;")]
+        #[surql_query_after("  UPDATE tagged SET tags = [] WHERE tags = NONE  ")]
+        struct Tagged {
+            tags: Vec<String>,
+        }
+
+        assert_eq!(
+            Tagged::schema_query(),
+            "DEFINE PARAM $max_tags VALUE 10; DEFINE TABLE tagged; DEFINE FIELD tags ON tagged TYPE array<string>; DEFINE FIELD tags.* ON tagged TYPE string; UPDATE tagged SET tags = [] WHERE tags = NONE;"
+        );
+    }
+
+    #[test]
+    fn test_custom_queries_split_into_statements() {
+        #[derive(SurQLDefinition)]
+        #[surql_query_before(
+            "DEFINE PARAM $sep VALUE ';'; DEFINE FUNCTION fn::noop() { RETURN 1; };"
+        )]
+        #[surql_query_after("UPDATE note SET body = ''; DELETE note WHERE body = NONE")]
+        struct Note {
+            body: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql_query(
+            "-- don't touch\nDEFINE TABLE legacy; DEFINE FIELD name ON legacy TYPE string; -- it's done"
+        )]
+        struct Legacy {
+            name: String,
+        }
+
+        assert_eq!(
+            Note::schema_statements(),
+            [
+                "DEFINE PARAM $sep VALUE ';';",
+                "DEFINE FUNCTION fn::noop() { RETURN 1; };",
+                "DEFINE TABLE note;",
+                "DEFINE FIELD body ON note TYPE string;",
+                "UPDATE note SET body = '';",
+                "DELETE note WHERE body = NONE;",
+            ]
+        );
+        assert_eq!(
+            Legacy::schema_statements(),
+            [
+                "DEFINE TABLE legacy;",
+                "DEFINE FIELD name ON legacy TYPE string;",
+            ]
+        );
+        assert_eq!(
+            Legacy::schema_query(),
+            "DEFINE TABLE legacy; DEFINE FIELD name ON legacy TYPE string;"
+        );
+    }
+
+    #[test]
+    fn test_scoped_schema() {
+        #[derive(SurQLDefinition)]
+        #[surql_scope(ns = "app", db = "main")]
+        #[surql_table_options(IF_NOT_EXISTS)]
+        struct Setting {
+            key: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Unscoped {
+            key: String,
+        }
+
+        assert_eq!(
+            Setting::schema_query(),
+            "DEFINE TABLE IF NOT EXISTS setting; DEFINE FIELD IF NOT EXISTS key ON setting TYPE string;"
+        );
+        assert_eq!(
+            Setting::scoped_schema_query(),
+            "DEFINE NAMESPACE IF NOT EXISTS app; USE NS app; DEFINE DATABASE IF NOT EXISTS main; USE NS app DB main; DEFINE TABLE IF NOT EXISTS setting; DEFINE FIELD IF NOT EXISTS key ON setting TYPE string;"
+        );
+        assert_eq!(Unscoped::scoped_schema_query(), Unscoped::schema_query());
+    }
+
+    #[test]
+    fn test_record_access() {
+        #[derive(SurQLDefinition)]
+        #[surql_access(name = "account", identifier = "email", TOKEN = "15m", SESSION = "12h")]
+        struct AppUser {
+            id: String,
+            email: String,
+            name: String,
+            #[surql_field(PASSWORD)]
+            password: String,
+            #[surql_field(VALUE = "time::now()")]
+            created_at: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql_table("app_user")]
+        #[surql_access(name = "account", identifier = "email", SESSION = "24h", SCOPE)]
+        struct LegacyUser {
+            email: String,
+            #[surql_field(PASSWORD)]
+            pass: String,
+        }
+
+        assert_eq!(
+            AppUser::schema_query(),
+            "DEFINE TABLE app_user; DEFINE FIELD email ON app_user TYPE string; DEFINE FIELD name ON app_user TYPE string; DEFINE FIELD password ON app_user TYPE string; DEFINE FIELD created_at ON app_user TYPE string VALUE time::now(); DEFINE ACCESS account ON DATABASE TYPE RECORD SIGNUP (CREATE app_user SET email = $email, name = $name, password = crypto::argon2::generate($password)) SIGNIN (SELECT * FROM app_user WHERE email = $email AND crypto::argon2::compare(password, $password)) DURATION FOR TOKEN 15m, FOR SESSION 12h;"
+        );
+        assert_eq!(
+            LegacyUser::schema_query(),
+            "DEFINE TABLE app_user; DEFINE FIELD email ON app_user TYPE string; DEFINE FIELD pass ON app_user TYPE string; DEFINE SCOPE account SESSION 24h SIGNUP (CREATE app_user SET email = $email, pass = crypto::argon2::generate($pass)) SIGNIN (SELECT * FROM app_user WHERE email = $email AND crypto::argon2::compare(pass, $pass));"
+        );
+    }
+
+    #[test]
+    fn test_record_access_flattened() {
+        #[derive(SurQLDefinition, serde::Serialize)]
+        struct Profile {
+            display_name: String,
+        }
+
+        #[derive(SurQLDefinition, serde::Serialize)]
+        #[surql_access(name = "member", identifier = "email")]
+        struct Member {
+            email: String,
+            #[surql_field(PASSWORD)]
+            password: String,
+            #[serde(flatten)]
+            profile: Profile,
+        }
+
+        assert_eq!(
+            Member::schema_query(),
+            "DEFINE TABLE member; DEFINE FIELD email ON member TYPE string; DEFINE FIELD password ON member TYPE string; DEFINE FIELD display_name ON member TYPE string; DEFINE ACCESS member ON DATABASE TYPE RECORD SIGNUP (CREATE member SET email = $email, password = crypto::argon2::generate($password), display_name = $display_name) SIGNIN (SELECT * FROM member WHERE email = $email AND crypto::argon2::compare(password, $password));"
+        );
+    }
+
+    #[test]
+    fn test_param() {
+        #[surql_param]
+        const MAX_UPLOAD_SIZE: i64 = 10_485_760;
+
+        #[surql_param(name = "ratio", OVERWRITE)]
+        const MIN_RATIO: f64 = -0.5;
+
+        #[surql_param(IF_NOT_EXISTS)]
+        const ALLOWED_ROLES: &[&str] = &["admin", "editor"];
+
+        #[surql_param]
+        const TRIAL_DAYS: Option<u8> = None;
+
+        assert_eq!(MAX_UPLOAD_SIZE, 10_485_760);
+        assert_eq!(
+            MAX_UPLOAD_SIZE_SURQL,
+            "DEFINE PARAM $max_upload_size VALUE 10485760;"
+        );
+        assert_eq!(MIN_RATIO_SURQL, "DEFINE PARAM OVERWRITE $ratio VALUE -0.5;");
+        assert_eq!(
+            ALLOWED_ROLES_SURQL,
+            "DEFINE PARAM IF NOT EXISTS $allowed_roles VALUE [\"admin\", \"editor\"];"
+        );
+        assert_eq!(TRIAL_DAYS_SURQL, "DEFINE PARAM $trial_days VALUE NONE;");
+    }
+
+    #[test]
+    fn test_function() {
+        #[surql_function(body = "RETURN string::repeat($text, $count);")]
+        fn repeat(text: &str, count: i64) -> String;
+
+        #[surql_function(name = "stats::mean", body = "{ RETURN math::mean($values); }")]
+        fn mean(values: Vec<f64>, label: Option<String>);
+
+        assert_eq!(
+            REPEAT_SURQL,
+            "DEFINE FUNCTION fn::repeat($text: string, $count: int) -> string { RETURN string::repeat($text, $count); };"
+        );
+        assert_eq!(repeat("a\"b", 3), "fn::repeat(\"a\\\"b\", 3)");
+
+        assert_eq!(
+            MEAN_SURQL,
+            "DEFINE FUNCTION fn::stats::mean($values: array<float>, $label: option<string>) { RETURN math::mean($values); };"
+        );
+        assert_eq!(
+            mean(vec![1.0, 2.5], None),
+            "fn::stats::mean([1.0, 2.5], NONE)"
+        );
+
+        #[surql_function(body = "RETURN $type;")]
+        fn r#kind(r#type: String) -> String;
+
+        assert_eq!(
+            KIND_SURQL,
+            "DEFINE FUNCTION fn::kind($type: string) -> string { RETURN $type; };"
+        );
+        assert_eq!(r#kind("a".to_string()), "fn::kind(\"a\")");
+
+        #[surql_function(body = "RETURN $a + $b;")]
+        fn add(a: i64, b: i64) -> i64;
+
+        assert_eq!(
+            ADD_SURQL,
+            "DEFINE FUNCTION fn::add($a: int, $b: int) -> int { RETURN $a + $b; };"
+        );
+        let call: String = add(1, 2);
+        assert_eq!(call, "fn::add(1, 2)");
+    }
+
+    #[test]
+    fn test_record_id() {
+        struct Uuid;
+
+        #[derive(SurQLDefinition)]
+        struct Session {
+            id: Uuid,
+            token: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Reading {
+            id: (String, i64),
+            value: f64,
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Counter {
+            id: Option<u64>,
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Note {
+            #[surql_field(TYPE = "record")]
+            id: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Label {
+            id: std::borrow::Cow<'static, str>,
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Setting {
+            id: std::collections::BTreeMap<String, String>,
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Tag {
+            name: String,
+        }
+
+        assert_eq!(
+            Session::schema_query(),
+            "DEFINE TABLE session; DEFINE FIELD token ON session TYPE string;"
+        );
+        assert_eq!(Session::record_id_kind(), Some(RecordIdKind::Uuid));
+        assert_eq!(
+            Session::record_id_kind().and_then(|kind| kind.generator()),
+            Some("uuid()")
+        );
+        assert_eq!(Reading::record_id_kind(), Some(RecordIdKind::Array));
+        assert_eq!(Counter::record_id_kind(), Some(RecordIdKind::Integer));
+        assert_eq!(Counter::schema_query(), "DEFINE TABLE counter;");
+        assert_eq!(Note::record_id_kind(), Some(RecordIdKind::Any));
+        assert_eq!(Label::record_id_kind(), Some(RecordIdKind::String));
+        assert_eq!(Setting::record_id_kind(), Some(RecordIdKind::Object));
+        assert_eq!(Tag::record_id_kind(), None);
+    }
+
+    #[test]
+    fn test_serde_rename() {
+        #[derive(SurQLDefinition, serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Profile {
+            display_name: String,
+            #[serde(rename = "mail", default)]
+            email_address: String,
+            #[serde(rename(serialize = "born", deserialize = "birth"))]
+            birth_date: String,
+            #[surql_field(RENAME = "is_active", INDEX)]
+            active_flag: bool,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql(rename_all = "SCREAMING_SNAKE_CASE")]
+        struct Setting {
+            setting_key: String,
+        }
+
+        assert_eq!(
+            Profile::schema_query(),
+            "DEFINE TABLE profile; DEFINE FIELD displayName ON profile TYPE string; DEFINE FIELD mail ON profile TYPE string; DEFINE FIELD born ON profile TYPE string; DEFINE FIELD is_active ON profile TYPE bool; DEFINE INDEX profile_is_active_idx ON TABLE profile FIELDS is_active;"
+        );
+        assert_eq!(
+            Setting::schema_query(),
+            "DEFINE TABLE setting; DEFINE FIELD SETTING_KEY ON setting TYPE string;"
+        );
+    }
+
+    #[test]
+    fn test_skip_fields() {
+        use std::{collections::HashMap, marker::PhantomData};
+
+        #[derive(SurQLDefinition, serde::Serialize)]
+        struct Article<T> {
+            title: String,
+            #[serde(skip)]
+            cache: HashMap<String, String>,
+            #[serde(skip_serializing, default)]
+            draft: Option<std::time::Instant>,
+            #[surql_field(SKIP, TYPE = "string")]
+            rendered: Vec<std::time::Duration>,
+            #[serde(skip_deserializing)]
+            views: i64,
+            marker: PhantomData<T>,
+        }
+
+        assert_eq!(
+            Article::<()>::schema_query(),
+            "DEFINE TABLE article; DEFINE FIELD title ON article TYPE string; DEFINE FIELD views ON article TYPE int;"
+        );
+    }
+
+    #[test]
+    fn test_flatten_fields() {
+        #[derive(SurQLDefinition, serde::Serialize)]
+        struct SoftDelete {
+            #[surql_field(DEFAULT = "false")]
+            deleted: bool,
+        }
+
+        #[derive(SurQLDefinition, serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct AuditInfo {
+            created_at: String,
+            #[serde(flatten)]
+            soft_delete: SoftDelete,
+        }
+
+        #[derive(SurQLDefinition, serde::Serialize)]
+        struct Tagged {
+            tags: Vec<String>,
+        }
+
+        #[derive(SurQLDefinition, serde::Serialize)]
+        #[surql_table_options(OVERWRITE)]
+        #[surql_scope(ns = "app", db = "main")]
+        struct Invoice {
+            #[serde(flatten)]
+            audit: AuditInfo,
+            #[surql_field(INDEX)]
+            number: i64,
+            #[surql_field(FLATTEN)]
+            extra: Tagged,
+        }
+
+        assert_eq!(
+            AuditInfo::schema_query(),
+            "DEFINE TABLE audit_info; DEFINE FIELD createdAt ON audit_info TYPE string; DEFINE FIELD deleted ON audit_info TYPE bool DEFAULT false;"
+        );
+        assert_eq!(
+            Invoice::schema_query(),
+            "DEFINE TABLE OVERWRITE invoice; DEFINE FIELD OVERWRITE createdAt ON invoice TYPE string; DEFINE FIELD OVERWRITE deleted ON invoice TYPE bool DEFAULT false; DEFINE FIELD OVERWRITE number ON invoice TYPE int; DEFINE FIELD OVERWRITE tags ON invoice TYPE array<string>; DEFINE INDEX OVERWRITE invoice_number_idx ON TABLE invoice FIELDS number;"
+        );
+        assert_eq!(
+            Invoice::scoped_schema_query(),
+            format!(
+                "DEFINE NAMESPACE OVERWRITE app; USE NS app; DEFINE DATABASE OVERWRITE main; USE NS app DB main; {}",
+                Invoice::schema_query()
+            )
+        );
+    }
+
+    #[test]
+    fn test_flatten_indexes_and_events() {
+        #[derive(SurQLDefinition, serde::Serialize)]
+        #[surql_analyzer(name = "simple", tokenizers = ["blank"], filters = ["lowercase"])]
+        #[surql_event(name = "contact_changed", THEN = "CREATE log")]
+        struct Contact {
+            #[surql_field(UNIQUE)]
+            email: String,
+            #[surql_search(analyzer = "simple")]
+            bio: String,
+        }
+
+        #[derive(SurQLDefinition, serde::Serialize)]
+        #[surql_table_options(OVERWRITE)]
+        struct Customer {
+            name: String,
+            #[serde(flatten)]
+            contact: Contact,
+        }
+
+        assert_eq!(
+            Customer::schema_statements(),
+            [
+                "DEFINE TABLE OVERWRITE customer;",
+                "DEFINE FIELD OVERWRITE name ON customer TYPE string;",
+                "DEFINE FIELD OVERWRITE email ON customer TYPE string;",
+                "DEFINE FIELD OVERWRITE bio ON customer TYPE string;",
+                "DEFINE ANALYZER OVERWRITE simple TOKENIZERS blank FILTERS lowercase;",
+                "DEFINE INDEX OVERWRITE customer_email_idx ON TABLE customer FIELDS email UNIQUE;",
+                "DEFINE INDEX OVERWRITE customer_bio_search ON TABLE customer FIELDS bio SEARCH ANALYZER simple;",
+                "DEFINE EVENT OVERWRITE contact_changed ON TABLE customer THEN (CREATE log);",
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Field `x` is defined more than once on table `both`")]
+    fn test_flatten_duplicate_fields() {
+        #[derive(SurQLDefinition, serde::Serialize)]
+        struct A {
+            x: bool,
+        }
+
+        #[derive(SurQLDefinition, serde::Serialize)]
+        struct B {
+            x: bool,
+        }
+
+        #[derive(SurQLDefinition, serde::Serialize)]
+        struct Both {
+            #[serde(flatten)]
+            a: A,
+            #[serde(flatten)]
+            b: B,
+            x: bool,
+        }
+
+        Both::schema_query();
+    }
+
+    #[test]
+    fn test_serde_defaults() {
+        fn default_role() -> String {
+            "member".to_string()
+        }
+
+        #[derive(SurQLDefinition, serde::Deserialize)]
+        #[surql_table_options(SERDE_DEFAULTS)]
+        struct Member {
+            name: String,
+            #[serde(default)]
+            score: i64,
+            #[serde(default)]
+            ratio: f64,
+            #[serde(default)]
+            tags: Vec<String>,
+            #[serde(default)]
+            nickname: Option<String>,
+            #[serde(default = "default_role")]
+            #[surql_field(DEFAULT = "'member'")]
+            role: String,
+        }
+
+        #[derive(SurQLDefinition, serde::Deserialize, Default)]
+        #[surql_table_options(SERDE_DEFAULTS)]
+        #[serde(default)]
+        struct Preferences {
+            #[surql_field(DEFAULT = "true")]
+            dark_mode: bool,
+            #[serde(default)]
+            label: String,
+        }
+
+        #[derive(SurQLDefinition, serde::Deserialize)]
+        struct Unmapped {
+            #[serde(default)]
+            score: i64,
+        }
+
+        assert_eq!(
+            Member::schema_query(),
+            "DEFINE TABLE member; DEFINE FIELD name ON member TYPE string; DEFINE FIELD score ON member TYPE int DEFAULT 0; DEFINE FIELD ratio ON member TYPE float DEFAULT 0.0; DEFINE FIELD tags ON member TYPE array<string> DEFAULT []; DEFINE FIELD nickname ON member TYPE option<string> DEFAULT NONE; DEFINE FIELD role ON member TYPE string DEFAULT 'member';"
+        );
+        assert_eq!(
+            Preferences::schema_query(),
+            "DEFINE TABLE preferences; DEFINE FIELD dark_mode ON preferences TYPE bool DEFAULT true; DEFINE FIELD label ON preferences TYPE string DEFAULT \"\";"
+        );
+        assert_eq!(
+            Unmapped::schema_query(),
+            "DEFINE TABLE unmapped; DEFINE FIELD score ON unmapped TYPE int;"
+        );
+    }
+
+    #[test]
+    fn test_table_naming() {
+        #[derive(SurQLDefinition)]
+        struct HTTPRequestLog {
+            status_code: i64,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql(rename_all = "camelCase", plural, prefix = "app_")]
+        struct UserCategory {
+            display_name: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql(table_case = "PascalCase", plural, suffix = "_v2")]
+        struct OAuthBox {
+            id: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql(plural)]
+        #[surql_table("audit")]
+        struct AuditEntry {
+            action: String,
+        }
+
+        assert_eq!(
+            HTTPRequestLog::schema_query(),
+            "DEFINE TABLE http_request_log; DEFINE FIELD status_code ON http_request_log TYPE int;"
+        );
+        assert_eq!(
+            UserCategory::schema_query(),
+            "DEFINE TABLE app_user_categories; DEFINE FIELD displayName ON app_user_categories TYPE string;"
+        );
+        assert_eq!(OAuthBox::schema_query(), "DEFINE TABLE OAuthBoxes_v2;");
+        assert_eq!(
+            AuditEntry::schema_query(),
+            "DEFINE TABLE audit; DEFINE FIELD action ON audit TYPE string;"
+        );
+    }
+
+    #[test]
+    fn test_schema_definition() {
+        #[derive(SurQLDefinition)]
+        #[surql_table_permissions(SELECT = "FULL", UPDATE = "WHERE user = $auth.id")]
+        #[surql_analyzer(name = "simple", tokenizers = ["blank"], filters = ["lowercase"])]
+        #[surql_event(name = "created", WHEN = "$event = 'CREATE'", THEN = "CREATE log")]
+        /// A blog post
+        struct Post {
+            #[surql_field(UNIQUE)]
+            slug: String,
+            #[surql_search(analyzer = "simple", BM25, HIGHLIGHTS)]
+            #[surql_field_permissions(FULL)]
+            body: String,
+            #[surql_vector(HNSW, DIST = "COSINE")]
+            embedding: [f32; 3],
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Tracked {
+            #[surql_field(READONLY)]
+            created_at: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        #[surql_table_options(IF_NOT_EXISTS, RELATION, IN = "user", OUT = "post")]
+        struct Likes {
+            #[surql_field(FLATTEN)]
+            tracked: Tracked,
+            weight: Option<f64>,
+        }
+
+        let post = Post::schema_definition().unwrap();
+        assert_eq!(post.to_string(), Post::schema_query());
+        assert_eq!(post.name, "post");
+        assert_eq!(post.comment.as_deref(), Some("A blog post"));
+        assert_eq!(
+            post.permissions,
+            Some(PermissionDefinition::Rules(vec![
+                PermissionRule {
+                    operations: vec![PermissionOperation::Select],
+                    condition: "FULL".to_string(),
+                },
+                PermissionRule {
+                    operations: vec![PermissionOperation::Update],
+                    condition: "WHERE user = $auth.id".to_string(),
+                },
+            ]))
+        );
+        let names: Vec<_> = post.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["slug", "body", "embedding"]);
+        assert_eq!(post.fields[1].field_type.as_deref(), Some("string"));
+        assert_eq!(post.fields[1].permissions, Some(PermissionDefinition::Full));
+        assert_eq!(
+            post.indexes[0],
+            IndexDefinition {
+                name: "post_slug_idx".to_string(),
+                fields: vec!["slug".to_string()],
+                kind: IndexKind::Unique,
+            }
+        );
+        assert!(
+            matches!(&post.indexes[1].kind, IndexKind::Search(search) if search.bm25 == Some(Bm25::Default))
+        );
+        assert!(
+            matches!(&post.indexes[2].kind, IndexKind::Vector(vector) if vector.dimension == Some(3))
+        );
+        assert_eq!(post.analyzers[0].tokenizers, ["blank"]);
+        assert_eq!(post.events[0].then, "CREATE log");
+
+        let mut overwritten = post.clone();
+        overwritten.mode = DefinitionMode::Overwrite;
+        assert_eq!(
+            overwritten.render_statements()[4..],
+            [
+                "DEFINE ANALYZER OVERWRITE simple TOKENIZERS blank FILTERS lowercase;",
+                "DEFINE INDEX OVERWRITE post_slug_idx ON TABLE post FIELDS slug UNIQUE;",
+                "DEFINE INDEX OVERWRITE post_body_search ON TABLE post FIELDS body SEARCH ANALYZER simple BM25 HIGHLIGHTS;",
+                "DEFINE INDEX OVERWRITE post_embedding_vector ON TABLE post FIELDS embedding HNSW DIMENSION 3 DIST COSINE;",
+                "DEFINE EVENT OVERWRITE created ON TABLE post WHEN $event = 'CREATE' THEN (CREATE log);",
+            ]
+        );
+
+        let likes = Likes::schema_definition().unwrap();
+        assert_eq!(likes.to_string(), Likes::schema_query());
+        assert_eq!(likes.mode, DefinitionMode::IfNotExists);
+        assert_eq!(
+            likes.table_type,
+            Some(TableType::Relation {
+                in_tables: Some("user".to_string()),
+                out_tables: Some("post".to_string()),
+                enforced: false,
+            })
+        );
+        let names: Vec<_> = likes.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["created_at", "weight"]);
+        assert!(likes.fields[0].readonly);
+        assert_eq!(
+            likes.fields[1].render("likes", DefinitionMode::Default),
+            "DEFINE FIELD weight ON likes TYPE option<float>;"
+        );
+    }
+
+    #[test]
+    fn test_schema_statements() {
+        #[derive(SurQLDefinition)]
+        #[surql_query_before("REMOVE TABLE IF EXISTS account")]
+        #[surql_table_options(OVERWRITE)]
+        struct Account {
+            #[surql_field(UNIQUE)]
+            email: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Timestamps {
+            created_at: String,
+        }
+
+        #[derive(SurQLDefinition)]
+        struct Order {
+            total: f64,
+            #[surql_field(FLATTEN)]
+            timestamps: Timestamps,
+        }
+
+        struct Manual;
+
+        impl SurQLSchemaProducer for Manual {
+            fn schema_query() -> &'static str {
+                "DEFINE TABLE manual;"
+            }
+        }
+
+        assert_eq!(
+            Account::schema_statements(),
+            [
+                "REMOVE TABLE IF EXISTS account;",
+                "DEFINE TABLE OVERWRITE account;",
+                "DEFINE FIELD OVERWRITE email ON account TYPE string;",
+                "DEFINE INDEX OVERWRITE account_email_idx ON TABLE account FIELDS email UNIQUE;",
+            ]
+        );
+        assert_eq!(
+            Account::schema_query(),
+            Account::schema_statements().join(" ")
+        );
+        assert_eq!(
+            Order::schema_statements(),
+            [
+                "DEFINE TABLE order;",
+                "DEFINE FIELD total ON order TYPE float;",
+                "DEFINE FIELD created_at ON order TYPE string;",
+            ]
+        );
+        assert_eq!(Order::schema_query(), Order::schema_statements().join(" "));
+        assert!(Manual::schema_statements().is_empty());
+    }
+
     #[cfg(feature = "runtime_query_validation")]
     #[test]
     #[should_panic(expected = "Failed to parse query")]
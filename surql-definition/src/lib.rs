@@ -1,2 +1,7 @@
-pub use surql_definition_core::SurQLSchemaProducer;
-pub use surql_definition_macros::SurQLDefinition;
+pub use surql_definition_core::{
+    AccessDefinition, AnalyzerDefinition, Bm25, Changefeed, DefinitionMode, EventDefinition,
+    FieldDefinition, IndexDefinition, IndexKind, PermissionDefinition, PermissionOperation,
+    PermissionRule, RecordIdKind, SearchIndex, SurQLSchemaProducer, SurQLTable, TableDefinition,
+    TableType, ToSurQLLiteral, VectorAlgorithm, VectorIndex,
+};
+pub use surql_definition_macros::{surql_function, surql_param, SurQLDefinition};